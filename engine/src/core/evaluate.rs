@@ -21,3 +21,38 @@ impl Evaluate for Engine {
         score
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use sdk::{fen::Fen, position::Position};
+
+    use crate::core::{evaluate::Evaluate, Engine};
+
+    #[test]
+    fn test_evaluation_is_symmetric() {
+        let child = thread::Builder::new()
+            .stack_size(32 * 1024 * 1024)
+            .spawn(|| {
+                let engine = Engine::default();
+
+                for fen in [
+                    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+                ] {
+                    let pos = Position::from_fen(fen.to_string()).unwrap();
+
+                    assert_eq!(
+                        engine.evaluate(&pos),
+                        -engine.evaluate(&pos.color_flipped()),
+                        "{fen}"
+                    );
+                }
+            })
+            .unwrap();
+
+        child.join().unwrap();
+    }
+}
@@ -1,5 +1,3 @@
-use std::{collections::hash_map::DefaultHasher, hash::{Hash, Hasher}};
-
 use move_gen::generators::movegen::MoveGen;
 use sdk::position::Position;
 
@@ -15,10 +13,10 @@ pub struct Engine {
     pub move_gen: MoveGen,
     pub move_list: MoveList,
     pub pos: Position,
+    /// Set by the `UCI_Chess960` option, moves are then written with castling as king takes rook.
+    pub chess960: bool,
 }
 
 pub fn hash_pos(pos: &Position) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    pos.hash(&mut hasher);
-    hasher.finish()
+    pos.hash
 }
@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use move_gen::r#move::Move;
 use smallvec::SmallVec;
 
@@ -30,12 +32,15 @@ impl MoveList {
     }
 }
 
-impl ToString for MoveList {
-    fn to_string(&self) -> String {
-        self.moves
+impl Display for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let moves = self
+            .moves
             .iter()
             .map(ToString::to_string)
             .collect::<Vec<_>>()
-            .join(" ")
+            .join(" ");
+
+        write!(f, "{moves}")
     }
 }
@@ -1,5 +1,4 @@
-use itertools::Itertools;
-use move_gen::r#move::{MakeMove, Move};
+use move_gen::r#move::{list::MoveList, MakeMove, Move};
 use sdk::position::{Color, Position};
 
 use super::{evaluate::Evaluate, Engine};
@@ -11,7 +10,8 @@ pub trait Search {
 impl Search for Engine {
     fn search(&mut self, position: &Position, depth: usize) -> Option<(f64, Move)> {
         let maximizing_player = position.turn == Color::White;
-        let (score, mv) = minmax(self, position, depth, maximizing_player);
+        let mut position = position.clone();
+        let (score, mv) = minmax(self, &mut position, depth, maximizing_player);
 
         Some((score, mv?))
     }
@@ -19,7 +19,7 @@ impl Search for Engine {
 
 fn minmax(
     engine: &mut Engine,
-    position: &Position,
+    position: &mut Position,
     depth: usize,
     maximizing_player: bool,
 ) -> (f64, Option<Move>) {
@@ -28,7 +28,10 @@ fn minmax(
         return (engine.evaluate(position), None);
     }
 
-    let moves = engine.move_gen.generate_legal_moves(position).collect_vec();
+    let mut moves = MoveList::new();
+    engine
+        .move_gen
+        .generate_legal_moves_into(position, &mut moves);
 
     if position.halfmove_clock >= 100 {
         return (0.0, None);
@@ -46,16 +49,16 @@ fn minmax(
         let mut best_move = None;
         let mut best_score = f64::MIN;
 
-        for mv in moves {
-            let mut pos = position.clone();
+        for mv in moves.iter().cloned() {
             engine.move_list.push(mv.clone());
 
-            pos.make_move(&mv).unwrap();
+            let state = position.make_move(&mv).unwrap();
             let (score, _) = if engine.move_list.count_occurrences(&mv) >= 2 {
                 (0.0, None)
             } else {
-                minmax(engine, &pos, depth - 1, false)
+                minmax(engine, position, depth - 1, false)
             };
+            position.undo_move(&mv, &state).unwrap();
             engine.move_list.pop();
 
             if score > best_score {
@@ -69,16 +72,16 @@ fn minmax(
         let mut best_move = None;
         let mut best_score = f64::MAX;
 
-        for mv in moves {
-            let mut pos = position.clone();
+        for mv in moves.iter().cloned() {
             engine.move_list.push(mv.clone());
 
-            pos.make_move(&mv).unwrap();
+            let state = position.make_move(&mv).unwrap();
             let (score, _) = if engine.move_list.count_occurrences(&mv) >= 2 {
                 (0.0, None)
             } else {
-                minmax(engine, &pos, depth - 1, true)
+                minmax(engine, position, depth - 1, true)
             };
+            position.undo_move(&mv, &state).unwrap();
             engine.move_list.pop();
 
             if score < best_score {
@@ -1,13 +1,8 @@
 pub mod core;
 mod uci;
 
-use move_gen::r#move::MakeMove;
-use sdk::position::Position;
-use timeit::timeit_loops;
 use uci::start_uci;
 
-use crate::core::search::Search;
-use crate::core::Engine;
 use std::thread;
 
 fn run() {
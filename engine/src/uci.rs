@@ -1,7 +1,12 @@
-use std::io;
+use std::{io, time::Instant};
 
 use itertools::Itertools;
-use move_gen::r#move::{MakeMove, Move};
+use move_gen::{
+    generators::movegen::MoveGen,
+    perft::{divide, perft_detailed, run_suite, PERFT_SUITE},
+    r#move::MakeMove,
+    utils::chess_notation::ChessNotation,
+};
 use sdk::{fen::Fen, position::Position};
 
 use crate::core::{search::Search, Engine};
@@ -31,8 +36,8 @@ pub fn start_uci() {
             "ucinewgame" => {}
             "position" => position(args, &mut engine),
             "go" => go(&mut engine),
-            "setoption" => {}
-            "perft" => {}
+            "setoption" => set_option(args, &mut engine),
+            "perft" => perft(args, &engine),
             "profile" => {}
             "stop" => {}
             "ponderhit" => {}
@@ -45,53 +50,223 @@ pub fn start_uci() {
 fn uci() {
     println!("id name NoName v0.1.0");
     println!("id author Mateusz Burdyna");
+    println!("option name UCI_Chess960 type check default false");
     println!("uciok");
 }
 
+fn set_option(args: Vec<&str>, engine: &mut Engine) {
+    let value_idx = args
+        .iter()
+        .position(|arg| *arg == "value")
+        .unwrap_or(args.len());
+    let name = args.get(1..value_idx).unwrap_or_default().join(" ");
+    let value = args.get(value_idx + 1..).unwrap_or_default().join(" ");
+
+    match name.to_lowercase().as_str() {
+        "uci_chess960" => match value.as_str() {
+            "true" => engine.chess960 = true,
+            "false" => engine.chess960 = false,
+            _ => println!("Invalid value for {name}: '{value}'"),
+        },
+        _ => println!("Unknown option: {name}"),
+    }
+}
+
 fn position(args: Vec<&str>, engine: &mut Engine) {
     if args.is_empty() {
         println!("{}", engine.pos);
         return;
     }
-    let fen = args.first().unwrap();
 
-    engine.pos = if *fen == "startpos" {
-        Position::default()
-    } else {
-        Position::from_fen(fen.to_string()).expect("Couldn't parse FEN")
-    };
+    match parse_position(&args, &engine.move_gen) {
+        Ok(pos) => engine.pos = pos,
+        Err(err) => println!("{err}"),
+    }
+}
 
-    let second = args.get(1);
-    let mut pos = engine.pos.clone();
+/// Parses the arguments of `position [startpos | fen <fen>] [moves <move>...]`. The FEN may be
+/// an X-FEN or Shredder-FEN, moves may castle as king takes rook.
+fn parse_position(args: &[&str], move_gen: &MoveGen) -> Result<Position, String> {
+    let moves_idx = args
+        .iter()
+        .position(|arg| *arg == "moves")
+        .unwrap_or(args.len());
 
-    if let Some(second) = second {
-        if *second == "moves" {
-            for (idx, mv) in args.iter().skip(2).enumerate() {
-                if let Some(mv) = parse_move(mv.to_string(), engine) {
-                    let _ = pos.make_move(&mv);
-                } else {
-                    println!("Invalid move: {}.{mv}", { idx + 1 });
-                }
-            }
-        } else {
-            println!("Expected 'moves', found '{second}'");
+    let mut pos = match args[..moves_idx].split_first() {
+        Some((&"startpos", [])) => Position::default(),
+        Some((&"fen", fields)) => {
+            Position::from_fen_strict(fields.join(" ")).map_err(|err| err.to_string())?
         }
+        Some((&"startpos", [extra, ..])) => {
+            return Err(format!("Expected 'moves', found '{extra}'"))
+        }
+        Some((first, _)) => return Err(format!("Expected 'startpos' or 'fen', found '{first}'")),
+        None => return Err("Expected 'startpos' or 'fen'".to_string()),
+    };
 
-        engine.pos = pos;
+    for (idx, uci) in args.iter().skip(moves_idx + 1).enumerate() {
+        let mv = move_gen
+            .parse_uci(&pos, uci)
+            .ok_or_else(|| format!("Invalid move: {}.{uci}", idx + 1))?;
+        pos.make_move(&mv)
+            .map_err(|err| format!("Invalid move: {}.{uci}: {err}", idx + 1))?;
     }
+
+    Ok(pos)
 }
 
 fn go(engine: &mut Engine) {
     let result = engine.search(&engine.pos.clone(), 3);
 
     if let Some((_score, mv)) = result {
-        println!("bestmove {}", mv);
+        let mv = if engine.chess960 {
+            mv.to_uci_chess960()
+        } else {
+            mv.to_uci()
+        };
+        println!("bestmove {mv}");
+    }
+}
+
+/// Expected nodes up to which `perft suite` runs each position, unless given.
+const DEFAULT_SUITE_NODES: u64 = 5_000_000;
+
+/// `perft <depth>` prints the nodes after each move, `perft detailed <depth>` counts the leaves
+/// by kind and `perft suite [max nodes]` checks the built-in positions.
+fn perft(args: Vec<&str>, engine: &Engine) {
+    let parse_depth = |arg: Option<&&str>| match arg.map(|depth| depth.parse::<usize>()) {
+        Some(Ok(depth)) if depth > 0 => Some(depth),
+        _ => {
+            println!("Expected a depth of at least 1");
+            None
+        }
+    };
+
+    match args.first().copied() {
+        Some("suite") => {
+            let max_nodes = match args.get(1).map(|nodes| nodes.parse::<u64>()) {
+                None => DEFAULT_SUITE_NODES,
+                Some(Ok(nodes)) => nodes,
+                Some(Err(_)) => {
+                    println!("Invalid node limit: {}", args[1]);
+                    return;
+                }
+            };
+
+            let start = Instant::now();
+            let results = run_suite(&engine.move_gen, PERFT_SUITE, max_nodes);
+            for result in &results {
+                println!(
+                    "{} {} depth {}: {} (expected {})",
+                    if result.passed() { "ok" } else { "FAILED" },
+                    result.case.name,
+                    result.depth,
+                    result.actual,
+                    result.expected
+                );
+            }
+
+            let failed = results.iter().filter(|result| !result.passed()).count();
+            println!(
+                "\n{} passed, {failed} failed in {:.2?}",
+                results.len() - failed,
+                start.elapsed()
+            );
+        }
+        Some("detailed") => {
+            let Some(depth) = parse_depth(args.get(1)) else {
+                return;
+            };
+
+            let start = Instant::now();
+            let stats = perft_detailed(&engine.move_gen, &engine.pos, depth);
+            println!("Nodes: {}", stats.nodes);
+            println!("Captures: {}", stats.captures);
+            println!("En passant: {}", stats.en_passant);
+            println!("Castles: {}", stats.castles);
+            println!("Promotions: {}", stats.promotions);
+            println!("Checks: {}", stats.checks);
+            println!("Discovered checks: {}", stats.discovered_checks);
+            println!("Double checks: {}", stats.double_checks);
+            println!("Checkmates: {}", stats.checkmates);
+            println!("Time: {:.2?}", start.elapsed());
+        }
+        depth => {
+            let Some(depth) = parse_depth(depth.as_ref()) else {
+                return;
+            };
+
+            let start = Instant::now();
+            let divided = divide(&engine.move_gen, &engine.pos, depth);
+            for (mv, nodes) in &divided {
+                let mv = if engine.chess960 {
+                    mv.to_uci_chess960()
+                } else {
+                    mv.to_uci()
+                };
+                println!("{mv}: {nodes}");
+            }
+
+            let nodes: u64 = divided.iter().map(|(_, nodes)| nodes).sum();
+            let elapsed = start.elapsed();
+            println!("\nNodes searched: {nodes}");
+            println!(
+                "Time: {elapsed:.2?} ({:.0} nps)",
+                nodes as f64 / elapsed.as_secs_f64()
+            );
+        }
     }
 }
 
-fn parse_move(mv: String, engine: &Engine) -> Option<Move> {
-    engine
-        .move_gen
-        .generate_legal_moves(&engine.pos)
-        .find(|m| m.to_string() == mv)
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use move_gen::generators::movegen::MoveGen;
+    use sdk::fen::Fen;
+
+    use crate::uci::parse_position;
+
+    #[test]
+    fn test_parse_position() {
+        let child = thread::Builder::new()
+            .stack_size(32 * 1024 * 1024)
+            .spawn(|| {
+                let move_gen = MoveGen::new();
+                let fen = |args: &str| {
+                    let args = args.split_whitespace().collect::<Vec<_>>();
+                    parse_position(&args, &move_gen).map(|pos| pos.to_fen())
+                };
+
+                assert_eq!(
+                    fen("startpos moves e2e4 e7e5"),
+                    Ok("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2".to_string())
+                );
+
+                // Chess960 castling as king takes rook, from a Shredder-FEN and an X-FEN.
+                let castled = "bqnnrrkb/pppppppp/8/8/8/8/PPPPPPPP/BQNNRRKB w - - 2 2";
+                for start in [
+                    "bqnnrkrb/pppppppp/8/8/8/8/PPPPPPPP/BQNNRKRB w GEge - 0 1",
+                    "bqnnrkrb/pppppppp/8/8/8/8/PPPPPPPP/BQNNRKRB w KQkq - 0 1",
+                ] {
+                    assert_eq!(
+                        fen(&format!("fen {start} moves f1g1 f8g8")),
+                        Ok(castled.to_string())
+                    );
+                }
+
+                assert_eq!(
+                    fen("fen 8/8/8 w - - 0 1"),
+                    Err("Invalid FEN: Invalid number of ranks, got 3, expected 8".to_string())
+                );
+                assert_eq!(
+                    fen("startpos moves e2e4 e2e4"),
+                    Err("Invalid move: 2.e2e4".to_string())
+                );
+                assert!(fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+            })
+            .unwrap();
+
+        child.join().unwrap();
+    }
 }
@@ -0,0 +1,242 @@
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use sdk::{
+    fen::Fen,
+    position::{Color, Position},
+};
+
+#[cfg(feature = "std")]
+use crate::pgn::PgnResult;
+use crate::{
+    generators::movegen::MoveGen,
+    r#move::{MakeMove, Move, StateInfo},
+};
+
+/// Plies without captures or pawn moves after which either player may claim a draw.
+const FIFTY_MOVE_PLIES: u16 = 100;
+/// Plies without captures or pawn moves after which the game is drawn.
+const SEVENTY_FIVE_MOVE_PLIES: u16 = 150;
+
+/// Why a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    /// Claimable, the same position occurred three times.
+    ThreefoldRepetition,
+    /// The same position occurred five times.
+    FivefoldRepetition,
+    /// Claimable, fifty moves by each side without captures or pawn moves.
+    FiftyMoveRule,
+    /// Seventy-five moves by each side without captures or pawn moves.
+    SeventyFiveMoveRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    Ongoing,
+    Win {
+        winner: Color,
+        termination: Termination,
+    },
+    Draw(Termination),
+}
+
+/// A move of the game with the state needed to take it back. The hashes of the states form
+/// the history repetitions are looked up in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ply {
+    pub mv: Move,
+    pub state: StateInfo,
+}
+
+/// A game from a start position, with its move history.
+///
+/// Moves taken back with [`Game::undo`] can be replayed with [`Game::redo`] until a different
+/// move is pushed, while [`Game::pop`] discards them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    start: Position,
+    position: Position,
+    plies: Vec<Ply>,
+    /// Moves taken back by [`Game::undo`], the next one to redo last.
+    undone: Vec<Move>,
+}
+
+impl Game {
+    pub fn new(start: Position) -> Self {
+        Game {
+            position: start.clone(),
+            start,
+            plies: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
+    /// Position after the last move.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    pub fn plies(&self) -> &[Ply] {
+        &self.plies
+    }
+
+    pub fn moves(&self) -> impl Iterator<Item = &Move> + '_ {
+        self.plies.iter().map(|ply| &ply.mv)
+    }
+
+    /// Plays a legal move and drops the moves that could be redone.
+    pub fn push(&mut self, move_gen: &MoveGen, mv: Move) -> anyhow::Result<()> {
+        self.play(move_gen, mv)?;
+        self.undone.clear();
+
+        Ok(())
+    }
+
+    /// Takes back the last move for good.
+    pub fn pop(&mut self) -> Option<Move> {
+        let ply = self.plies.pop()?;
+        self.position
+            .undo_move(&ply.mv, &ply.state)
+            .expect("BUG: Played move not undoable");
+
+        Some(ply.mv)
+    }
+
+    /// Takes back the last move, keeping it for [`Game::redo`].
+    pub fn undo(&mut self) -> Option<&Move> {
+        let mv = self.pop()?;
+        self.undone.push(mv);
+
+        self.undone.last()
+    }
+
+    /// Replays the last move taken back with [`Game::undo`].
+    pub fn redo(&mut self, move_gen: &MoveGen) -> Option<&Move> {
+        let mv = self.undone.pop()?;
+        self.play(move_gen, mv)
+            .expect("BUG: Undone move no longer legal");
+
+        self.plies.last().map(|ply| &ply.mv)
+    }
+
+    fn play(&mut self, move_gen: &MoveGen, mv: Move) -> anyhow::Result<()> {
+        if !move_gen
+            .generate_legal_moves(&self.position)
+            .any(|legal| legal == mv)
+        {
+            anyhow::bail!("Illegal move {} in {}", mv.to_uci(), self.position.to_fen());
+        }
+
+        let state = self.position.make_move(&mv)?;
+        self.plies.push(Ply { mv, state });
+
+        Ok(())
+    }
+
+    /// Number of times the current position occurred, including now. Positions are compared
+    /// by their zobrist hash, which covers side to move, castling rights and en passant.
+    pub fn repetitions(&self) -> usize {
+        let hash = self.position.hash;
+
+        // Only positions since the last capture or pawn move can repeat, and only those with
+        // the same side to move.
+        let reversible = usize::from(self.position.halfmove_clock).min(self.plies.len());
+        let earlier = self.plies[self.plies.len() - reversible..]
+            .iter()
+            .rev()
+            .skip(1)
+            .step_by(2)
+            .filter(|ply| ply.state.hash == hash)
+            .count();
+
+        earlier + 1
+    }
+
+    /// Draws either player may claim, but which don't end the game on their own.
+    pub fn claimable_draw(&self) -> Option<Termination> {
+        if self.repetitions() >= 3 {
+            Some(Termination::ThreefoldRepetition)
+        } else if self.position.halfmove_clock >= FIFTY_MOVE_PLIES {
+            Some(Termination::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+
+    /// Result of the game by the rules alone, without claims, resignation or time forfeit.
+    /// Checkmate takes precedence over the draw rules.
+    pub fn result(&self, move_gen: &MoveGen) -> GameResult {
+        if move_gen
+            .generate_legal_moves(&self.position)
+            .next()
+            .is_none()
+        {
+            return if move_gen.is_check(&self.position) {
+                GameResult::Win {
+                    winner: self.position.enemy(),
+                    termination: Termination::Checkmate,
+                }
+            } else {
+                GameResult::Draw(Termination::Stalemate)
+            };
+        }
+
+        if self.position.is_insufficient_material() {
+            GameResult::Draw(Termination::InsufficientMaterial)
+        } else if self.repetitions() >= 5 {
+            GameResult::Draw(Termination::FivefoldRepetition)
+        } else if self.position.halfmove_clock >= SEVENTY_FIVE_MOVE_PLIES {
+            GameResult::Draw(Termination::SeventyFiveMoveRule)
+        } else {
+            GameResult::Ongoing
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new(Position::default())
+    }
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let reason = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient material",
+            Termination::ThreefoldRepetition => "threefold repetition",
+            Termination::FivefoldRepetition => "fivefold repetition",
+            Termination::FiftyMoveRule => "fifty-move rule",
+            Termination::SeventyFiveMoveRule => "seventy-five-move rule",
+        };
+
+        write!(f, "{reason}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<GameResult> for PgnResult {
+    fn from(result: GameResult) -> Self {
+        match result {
+            GameResult::Ongoing => PgnResult::Unknown,
+            GameResult::Win {
+                winner: Color::White,
+                ..
+            } => PgnResult::WhiteWins,
+            GameResult::Win {
+                winner: Color::Black,
+                ..
+            } => PgnResult::BlackWins,
+            GameResult::Draw(_) => PgnResult::Draw,
+        }
+    }
+}
@@ -1,20 +1,21 @@
-use rand::Rng;
 use sdk::{
     bitboard::{Bitboard, Direction},
-    lookup::{in_between, sliders::Slider},
+    lookup::{in_between::IN_BETWEEN, sliders::Slider},
     position::{self, Color, Piece, Position},
     square::{Rank, Square},
 };
 
 use crate::{
-    lookup::{load_lookup_tables, LookupTables, MagicEntry},
-    r#move::{MakeMove, Move, MoveKind},
+    lookup::{load_lookup_tables, LookupTables, SliderBackend},
+    r#move::{list::MoveList, MakeMove, Move, MoveKind},
     xray::XRayGenerator,
 };
 
 use super::pieces::{
-    king_generator::KingMoveGenerator, knight_generator::KnightMoveGenerator,
-    pawn_generator::PawnMoveGenerator, simple_move_generator::SimpleMoveGenerator,
+    king_generator::KingMoveGenerator,
+    knight_generator::KnightMoveGenerator,
+    pawn_generator::{promotion_rank, PawnMoveGenerator, PROMOTIONS, UNDERPROMOTIONS},
+    simple_move_generator::SimpleMoveGenerator,
     slider_generator::SliderMoveGenerator,
 };
 
@@ -22,14 +23,44 @@ pub struct MoveGen {
     pub lookups: LookupTables,
 }
 
+/// Subset of the legal moves to generate. Captures and quiets together are all legal moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GenType {
+    /// Every legal move.
+    #[default]
+    Legal,
+    /// Every capture, including en passant and all capture promotions, and queen promotions
+    /// that don't capture.
+    Captures,
+    /// Moves that don't capture, except queen promotions.
+    Quiets,
+    /// Every legal move when in check, none otherwise.
+    Evasions,
+    /// Moves in [`GenType::Quiets`] that give check.
+    QuietChecks,
+}
+
 impl MoveGen {
     pub fn new() -> Self {
-        let lookup_tables = load_lookup_tables().expect("Couldn't load lookup tables");
+        Self::with_backend(SliderBackend::default())
+    }
+
+    pub fn with_backend(backend: SliderBackend) -> Self {
+        let lookup_tables = load_lookup_tables(backend).expect("Couldn't load lookup tables");
         Self {
             lookups: lookup_tables,
         }
     }
 
+    pub fn slider_backend(&self) -> SliderBackend {
+        #[cfg(feature = "magic")]
+        if self.lookups.magics.is_some() {
+            return SliderBackend::Magic;
+        }
+
+        SliderBackend::KoggeStone
+    }
+
     pub fn pinned_pieces(&self, pos: &Position) -> Bitboard {
         let king_square = pos.pieces[pos.turn as usize][Piece::King as usize].msb();
         let occ = pos.occupied;
@@ -45,13 +76,11 @@ impl MoveGen {
         let mut pinned_pieces = Bitboard(0);
 
         for sq in self.xray_rook_attacks(king_square, occ) & op_rq {
-            pinned_pieces |=
-                self.lookups.in_between[sq as usize][king_square as usize] & own_pieces;
+            pinned_pieces |= IN_BETWEEN[sq as usize][king_square as usize] & own_pieces;
         }
 
         for sq in self.xray_bishop_attacks(king_square, occ) & op_bq {
-            pinned_pieces |=
-                self.lookups.in_between[sq as usize][king_square as usize] & own_pieces;
+            pinned_pieces |= IN_BETWEEN[sq as usize][king_square as usize] & own_pieces;
         }
 
         pinned_pieces
@@ -113,72 +142,300 @@ impl MoveGen {
         }
     }
 
-    pub fn generate_legal_moves<'a>(
-        &'a self,
-        pos: &'a Position,
-    ) -> Box<dyn Iterator<Item = Move> + 'a> {
+    /// Pieces of `color` that are the only piece between a slider of `color` and the enemy
+    /// king, i.e. moving them off the line gives a discovered check.
+    pub fn discovered_check_candidates(&self, pos: &Position, color: Color) -> Bitboard {
+        let enemy_king = pos.pieces[color.enemy() as usize][Piece::King as usize].msb();
+        let own_pieces = pos.occupation(&color);
+        let pieces = pos.pieces[color as usize];
+
+        let rq = pieces[Piece::Rook as usize] | pieces[Piece::Queen as usize];
+        let bq = pieces[Piece::Bishop as usize] | pieces[Piece::Queen as usize];
+
+        let mut candidates = Bitboard(0);
+
+        for sq in self.xray_rook_attacks(enemy_king, pos.occupied) & rq {
+            candidates |= IN_BETWEEN[sq as usize][enemy_king as usize] & own_pieces;
+        }
+
+        for sq in self.xray_bishop_attacks(enemy_king, pos.occupied) & bq {
+            candidates |= IN_BETWEEN[sq as usize][enemy_king as usize] & own_pieces;
+        }
+
+        candidates
+    }
+
+    /// Squares from which a piece of the side to move would attack the enemy king.
+    pub fn check_squares(&self, pos: &Position, piece: Piece) -> Bitboard {
+        let enemy_king = pos.pieces[pos.enemy() as usize][Piece::King as usize].msb();
+
+        match piece {
+            Piece::Pawn => self.pawn_attacks(pos.enemy(), enemy_king),
+            Piece::Knight => self.knight_attacks(enemy_king),
+            Piece::Bishop => self.bishop_moves(enemy_king, pos.occupied),
+            Piece::Rook => self.rook_moves(enemy_king, pos.occupied),
+            Piece::Queen => self.queen_moves(enemy_king, pos.occupied),
+            Piece::King => Bitboard::empty(),
+        }
+    }
+
+    /// Whether playing the legal move `mv` in `pos` checks the enemy king, directly or by
+    /// discovery.
+    pub fn gives_check(&self, pos: &Position, mv: &Move) -> bool {
+        let color = pos.turn;
+        let enemy_king = pos.pieces[color.enemy() as usize][Piece::King as usize].msb();
+        let from = mv.from();
+        let (piece, _) = pos.piece_at(&from).expect("BUG: No piece at from square");
+
+        // Piece that could check directly, where it lands, the squares it leaves and those the
+        // move leaves occupied.
+        let (piece, to, vacated, landed) = match mv.castling_kind(&color) {
+            Some(castling) => {
+                let (rook_to, king_to) = castling.target_squares();
+
+                (
+                    Piece::Rook,
+                    rook_to,
+                    from.bitboard() | mv.to(),
+                    rook_to.bitboard() | king_to,
+                )
+            }
+            None if mv.is_enpass_capture() => {
+                let captured = mv
+                    .to()
+                    .offset(if color == Color::White { -1 } else { 1 }, 0)
+                    .expect("BUG: Invalid en passant square");
+
+                (
+                    piece,
+                    mv.to(),
+                    from.bitboard() | captured,
+                    mv.to().bitboard(),
+                )
+            }
+            None => (
+                mv.promotion().unwrap_or(piece),
+                mv.to(),
+                from.bitboard(),
+                mv.to().bitboard(),
+            ),
+        };
+        let occupied = (pos.occupied & !vacated) | landed;
+
+        let direct = match piece {
+            Piece::Pawn => self.pawn_attacks(color, to),
+            Piece::Knight => self.knight_attacks(to),
+            Piece::Bishop => self.bishop_moves(to, occupied),
+            Piece::Rook => self.rook_moves(to, occupied),
+            Piece::Queen => self.queen_moves(to, occupied),
+            Piece::King => Bitboard::empty(),
+        };
+        if direct.has(enemy_king) {
+            return true;
+        }
+
+        // Sliders that stayed put, now seeing the enemy king.
+        let pieces = pos.pieces[color as usize];
+        let rq = (pieces[Piece::Rook as usize] | pieces[Piece::Queen as usize]) & !vacated;
+        let bq = (pieces[Piece::Bishop as usize] | pieces[Piece::Queen as usize]) & !vacated;
+
+        !((self.rook_moves(enemy_king, occupied) & rq)
+            | (self.bishop_moves(enemy_king, occupied) & bq))
+            .is_empty()
+    }
+
+    /// Calls `visit` with every legal move of `pos` of the given [`GenType`], without
+    /// allocating.
+    pub fn visit_moves<F: FnMut(Move)>(&self, pos: &Position, gen_type: GenType, mut visit: F) {
         let friendly_occ = pos.occupation(&pos.turn);
         let enemy_occ = pos.occupation(&pos.enemy());
-        let pinned_pieces = self.pinned_pieces(pos);
+        let empty = !pos.occupied;
         let king_square = pos.pieces[pos.turn as usize][Piece::King as usize].msb();
+        let attackers_to_king = self.attacks_to_square(pos, king_square, pos.enemy(), pos.occupied);
 
-        let pawn_quiet_moves =
-            self.generate_pawn_moves(pos, friendly_occ, enemy_occ, pinned_pieces);
-        let pawn_capturing_moves =
-            self.generate_pawn_attacks(pos, friendly_occ, enemy_occ, pinned_pieces);
-        let knight_moves = self.generate_knight_moves(pos, friendly_occ, enemy_occ, pinned_pieces);
-        let slider_moves = self.generate_slider_moves(pos, friendly_occ, enemy_occ, pinned_pieces);
-        let king_moves = self.generate_king_moves(pos, friendly_occ, enemy_occ, pinned_pieces);
-        let castling_moves =
-            self.generate_all_castlings(pos, friendly_occ, enemy_occ, pinned_pieces);
-
-        let mut attackers_to_king =
-            self.attacks_to_square(pos, king_square, pos.enemy(), pos.occupied);
-
-        let non_king_moves = pawn_quiet_moves
-            .chain(pawn_capturing_moves)
-            .chain(knight_moves)
-            .chain(slider_moves)
-            .chain(castling_moves);
-
-        if attackers_to_king.is_empty() {
-            Box::new(non_king_moves.chain(king_moves))
-        } else {
-            let attacker_sq = attackers_to_king.pop_lsb();
-
-            if attackers_to_king.is_empty() {
-                let slider = match pos.piece_at(&attacker_sq).unwrap().0 {
-                    Piece::Rook => Some(Slider::Rook),
-                    Piece::Bishop => Some(Slider::Bishop),
-                    Piece::Queen => Some(Slider::Queen),
-                    _ => None,
-                };
+        if gen_type == GenType::Evasions && attackers_to_king.is_empty() {
+            return;
+        }
 
-                Box::new(non_king_moves.chain(king_moves).filter(move |mv| {
-                    let blockable_squares = if slider.is_some() {
-                        let between =
-                            self.lookups.in_between[attacker_sq as usize][king_square as usize];
+        // Squares each kind of move may land on, whatever piece makes it.
+        let kind_targets = match gen_type {
+            GenType::Legal | GenType::Evasions => !friendly_occ,
+            GenType::Captures => enemy_occ,
+            GenType::Quiets | GenType::QuietChecks => empty,
+        };
+
+        // Only the king can escape a double check.
+        if attackers_to_king.count() > 1 {
+            if gen_type == GenType::QuietChecks {
+                self.generate_king_moves(pos, kind_targets, &mut |mv| {
+                    if self.gives_check(pos, &mv) {
+                        visit(mv);
+                    }
+                });
+            } else {
+                self.generate_king_moves(pos, kind_targets, &mut visit);
+            }
+            return;
+        }
 
-                        between & !pos.occupation(&pos.turn)
-                    } else {
-                        Bitboard::empty()
+        // Other pieces have to take a single checker or block it.
+        let targets = self.evasion_targets(pos, attackers_to_king, kind_targets);
+        let pinned_pieces = self.pinned_pieces(pos);
+        let promotion_rank = promotion_rank(pos.turn).bitboard();
+
+        match gen_type {
+            GenType::Legal | GenType::Evasions => {
+                self.generate_pawn_moves(pos, targets, pinned_pieces, &PROMOTIONS, &mut visit);
+                self.generate_pawn_attacks(
+                    pos,
+                    targets,
+                    pinned_pieces,
+                    &PROMOTIONS,
+                    true,
+                    &mut visit,
+                );
+                self.generate_piece_moves(pos, |_| targets, pinned_pieces, &mut visit);
+                self.generate_all_castlings(pos, &mut visit);
+                self.generate_king_moves(pos, kind_targets, &mut visit);
+            }
+            GenType::Captures => {
+                // Queen promotions count as captures, as they change the material as much.
+                let pushes = self.evasion_targets(pos, attackers_to_king, empty) & promotion_rank;
+                self.generate_pawn_moves(pos, pushes, pinned_pieces, &[Piece::Queen], &mut visit);
+                self.generate_pawn_attacks(
+                    pos,
+                    targets,
+                    pinned_pieces,
+                    &PROMOTIONS,
+                    true,
+                    &mut visit,
+                );
+                self.generate_piece_moves(pos, |_| targets, pinned_pieces, &mut visit);
+                self.generate_king_moves(pos, kind_targets, &mut visit);
+            }
+            GenType::Quiets => {
+                self.generate_pawn_moves(pos, targets, pinned_pieces, &UNDERPROMOTIONS, &mut visit);
+                self.generate_piece_moves(pos, |_| targets, pinned_pieces, &mut visit);
+                self.generate_all_castlings(pos, &mut visit);
+                self.generate_king_moves(pos, kind_targets, &mut visit);
+            }
+            GenType::QuietChecks => {
+                let candidates = self.discovered_check_candidates(pos, pos.turn);
+
+                // Direct checks land on the check squares of the moving piece. Moves of
+                // discovered check candidates are all tried below instead.
+                let mut direct = |mv: Move| {
+                    if !candidates.has(mv.from()) {
+                        visit(mv);
+                    }
+                };
+                self.generate_pawn_moves(
+                    pos,
+                    targets & self.check_squares(pos, Piece::Pawn) & !promotion_rank,
+                    pinned_pieces,
+                    &[],
+                    &mut direct,
+                );
+                self.generate_piece_moves(
+                    pos,
+                    |piece| targets & self.check_squares(pos, piece),
+                    pinned_pieces,
+                    &mut direct,
+                );
+
+                // Promotions, castling and discovered checks are rare, each is tried.
+                let mut checking = |mv: Move| {
+                    if self.gives_check(pos, &mv) {
+                        visit(mv);
+                    }
+                };
+                self.generate_pawn_moves(
+                    pos,
+                    targets & promotion_rank,
+                    pinned_pieces,
+                    &UNDERPROMOTIONS,
+                    &mut checking,
+                );
+                self.generate_all_castlings(pos, &mut checking);
+
+                if !candidates.is_empty() {
+                    let mut discovered = |mv: Move| {
+                        if candidates.has(mv.from()) && self.gives_check(pos, &mv) {
+                            visit(mv);
+                        }
                     };
-
-                    mv.to() == attacker_sq
-                        || mv.from() == king_square
-                        || !(mv.to().bitboard() & blockable_squares).is_empty()
-                }))
-            } else {
-                Box::new(king_moves)
+                    self.generate_pawn_moves(
+                        pos,
+                        targets & !promotion_rank,
+                        pinned_pieces,
+                        &[],
+                        &mut discovered,
+                    );
+                    self.generate_piece_moves(pos, |_| targets, pinned_pieces, &mut discovered);
+                    self.generate_king_moves(pos, kind_targets, &mut discovered);
+                }
             }
         }
     }
-}
 
-pub enum PositionState {
-    Checkmate,
-    Stalemate,
-    InProgress,
+    /// Knight, bishop, rook and queen moves landing on the targets of their piece.
+    fn generate_piece_moves<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        targets: impl Fn(Piece) -> Bitboard,
+        pinned_pieces: Bitboard,
+        visit: &mut F,
+    ) {
+        self.generate_knight_moves(pos, targets(Piece::Knight), pinned_pieces, visit);
+        for slider in [Slider::Bishop, Slider::Rook, Slider::Queen] {
+            self.generate_slider_moves(pos, slider, targets(slider.into()), pinned_pieces, visit);
+        }
+    }
+
+    /// `targets` restricted to the squares that take a single checker or block it.
+    fn evasion_targets(
+        &self,
+        pos: &Position,
+        attackers_to_king: Bitboard,
+        targets: Bitboard,
+    ) -> Bitboard {
+        if attackers_to_king.is_empty() {
+            return targets;
+        }
+
+        let king_square = pos.pieces[pos.turn as usize][Piece::King as usize].msb();
+        let attacker_sq = attackers_to_king.lsb();
+
+        targets & (IN_BETWEEN[attacker_sq as usize][king_square as usize] | attacker_sq)
+    }
+
+    /// Calls `visit` with every legal move of `pos`, without allocating.
+    pub fn visit_legal_moves<F: FnMut(Move)>(&self, pos: &Position, visit: F) {
+        self.visit_moves(pos, GenType::Legal, visit);
+    }
+
+    /// Appends the legal moves of `pos` of the given [`GenType`] to `moves`.
+    pub fn generate_moves_into(&self, pos: &Position, gen_type: GenType, moves: &mut MoveList) {
+        self.visit_moves(pos, gen_type, |mv| moves.push(mv));
+    }
+
+    /// Appends every legal move of `pos` to `moves`.
+    pub fn generate_legal_moves_into(&self, pos: &Position, moves: &mut MoveList) {
+        self.generate_moves_into(pos, GenType::Legal, moves);
+    }
+
+    /// Legal moves of `pos` of the given [`GenType`], collected into a [`MoveList`] first.
+    pub fn generate_moves(&self, pos: &Position, gen_type: GenType) -> impl Iterator<Item = Move> {
+        let mut moves = MoveList::new();
+        self.generate_moves_into(pos, gen_type, &mut moves);
+
+        moves.into_iter()
+    }
+
+    /// Legal moves of `pos`, collected into a [`MoveList`] first.
+    pub fn generate_legal_moves(&self, pos: &Position) -> impl Iterator<Item = Move> {
+        self.generate_moves(pos, GenType::Legal)
+    }
 }
 
 impl Default for MoveGen {
@@ -1,6 +1,7 @@
 use sdk::{
     bitboard::Bitboard,
-    position::{CastlingKind, Color, Piece, Position},
+    lookup::in_between::IN_BETWEEN,
+    position::{CastlingKind, Piece, Position},
     square::Square,
 };
 
@@ -12,21 +13,11 @@ use crate::{
 use super::simple_move_generator::SimpleMoveGenerator;
 
 pub trait KingMoveGenerator {
-    fn generate_king_moves<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
-        pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_>;
+    /// Visits king moves landing on `targets` that don't walk into check.
+    fn generate_king_moves<F: FnMut(Move)>(&self, pos: &Position, targets: Bitboard, visit: &mut F);
 
-    fn generate_all_castlings<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
-        pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_>;
+    /// Visits legal castling moves, none when in check.
+    fn generate_all_castlings<F: FnMut(Move)>(&self, pos: &Position, visit: &mut F);
 
     fn generate_castling<'a>(
         &'a self,
@@ -38,69 +29,51 @@ pub trait KingMoveGenerator {
 }
 
 impl KingMoveGenerator for MoveGen {
-    fn generate_king_moves<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        _enemy_occ: Bitboard,
-        pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_> {
+    fn generate_king_moves<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        targets: Bitboard,
+        visit: &mut F,
+    ) {
         let color = pos.turn;
-        let bb = pos.pieces[color as usize][Piece::King as usize] & !pinned_pieces;
+        let bb = pos.pieces[color as usize][Piece::King as usize];
 
-        let iter = bb.into_iter().flat_map(move |from_square| {
-            let attacks = self.king_attacks(from_square) & !friendly_occ;
+        for from_square in bb {
+            let attacks = self.king_attacks(from_square) & targets;
 
-            attacks.into_iter().filter_map(move |target_square| {
+            for target_square in attacks {
                 // Move is illegal if it leaves the king in check
                 if !self
                     .attacks_to_square(pos, target_square, pos.enemy(), pos.occupied & !bb)
                     .is_empty()
                 {
-                    return None;
+                    continue;
                 }
 
-                let captured_piece = pos.piece_at(&target_square).map(|piece| piece.0);
-                let kind = if captured_piece.is_some() {
+                let kind = if pos.occupied.has(target_square) {
                     MoveKind::Capture
                 } else {
                     MoveKind::Quiet
                 };
 
-                Some(Move::new(from_square, target_square, None, &kind))
-            })
-        });
-
-        Box::new(iter)
+                visit(Move::new(from_square, target_square, None, &kind));
+            }
+        }
     }
 
-    fn generate_all_castlings<'a>(
-        &'a self,
-        pos: &'a Position,
-        _friendly_occ: Bitboard,
-        _enemy_occ: Bitboard,
-        _pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_> {
+    fn generate_all_castlings<F: FnMut(Move)>(&self, pos: &Position, visit: &mut F) {
         let king_square = pos.pieces[pos.turn as usize][Piece::King as usize].msb();
 
-        let occ = pos.occupation(&Color::White) | pos.occupation(&Color::Black);
-
-        let mut castling_moves = Vec::new();
+        if self.is_check(pos) {
+            return;
+        }
 
-        if !self.is_check(pos) {
-            for castling_kind in &[
-                CastlingKind::WhiteKingside,
-                CastlingKind::WhiteQueenside,
-                CastlingKind::BlackKingside,
-                CastlingKind::BlackQueenside,
-            ] {
-                if let Some(mv) = self.generate_castling(pos, castling_kind, king_square, occ) {
-                    castling_moves.push(mv);
-                }
+        for castling_kind in &CastlingKind::all() {
+            if let Some(mv) = self.generate_castling(pos, castling_kind, king_square, pos.occupied)
+            {
+                visit(mv);
             }
         }
-
-        Box::new(castling_moves.into_iter())
     }
 
     fn generate_castling<'a>(
@@ -110,64 +83,39 @@ impl KingMoveGenerator for MoveGen {
         king_square: Square,
         occ: Bitboard,
     ) -> Option<Move> {
-        let color = match castling_kind {
-            CastlingKind::WhiteKingside | CastlingKind::WhiteQueenside => Color::White,
-            CastlingKind::BlackKingside | CastlingKind::BlackQueenside => Color::Black,
-        };
-
-        if pos.turn != color {
+        if pos.turn != castling_kind.color() {
             return None;
         }
 
-        match castling_kind {
-            CastlingKind::WhiteKingside | CastlingKind::WhiteQueenside => {
-                if king_square != Square::E1 {
-                    return None;
-                }
-            }
-            CastlingKind::BlackKingside | CastlingKind::BlackQueenside => {
-                if king_square != Square::E8 {
-                    return None;
-                }
-            }
-        };
+        let rook_square = pos.castling.rook_square(castling_kind)?;
+        let (rook_target, king_target) = castling_kind.target_squares();
 
-        let between_bb = match castling_kind {
-            CastlingKind::WhiteKingside => Square::F1.bitboard() | Square::G1.bitboard(),
-            CastlingKind::WhiteQueenside => Square::C1.bitboard() | Square::D1.bitboard(),
-            CastlingKind::BlackKingside => Square::F8.bitboard() | Square::G8.bitboard(),
-            CastlingKind::BlackQueenside => Square::C8.bitboard() | Square::D8.bitboard(),
-        };
+        // In Chess960 the king and the rook may start on or next to their targets, so only
+        // other pieces block castling.
+        let castling_pieces = king_square.bitboard() | rook_square.bitboard();
+        let king_path =
+            IN_BETWEEN[king_square as usize][king_target as usize] | king_target.bitboard();
+        let rook_path =
+            IN_BETWEEN[rook_square as usize][rook_target as usize] | rook_target.bitboard();
 
-        if !(between_bb & occ).is_empty() {
+        if !((king_path | rook_path) & occ & !castling_pieces).is_empty() {
             return None;
         }
 
-        let target_square: Square = match castling_kind {
-            CastlingKind::WhiteKingside | CastlingKind::BlackKingside => {
-                (king_square as u8 + 2).try_into().unwrap()
-            }
-            CastlingKind::WhiteQueenside | CastlingKind::BlackQueenside => {
-                (king_square as u8 - 2).try_into().unwrap()
-            }
-        };
-
-        if pos.castling.has_castling_kind(castling_kind)
-            && pos.turn == color
-            && (occ & between_bb).is_empty()
-            && between_bb.into_iter().all(|sq| {
-                self.attacks_to_square(pos, sq, pos.enemy(), pos.occupied)
-                    .is_empty()
-            })
-        {
-            return Some(Move::new(
-                king_square,
-                target_square,
-                None,
-                &MoveKind::Castling,
-            ));
+        // The castling rook may shield the king target from a slider on the back rank.
+        if king_path.into_iter().any(|sq| {
+            !self
+                .attacks_to_square(pos, sq, pos.enemy(), occ & !castling_pieces)
+                .is_empty()
+        }) {
+            return None;
         }
 
-        None
+        Some(Move::new(
+            king_square,
+            rook_square,
+            None,
+            &MoveKind::Castling,
+        ))
     }
 }
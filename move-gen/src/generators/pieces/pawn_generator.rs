@@ -1,5 +1,6 @@
 use sdk::{
     bitboard::{Bitboard, Direction},
+    lookup::geometry::LINE,
     position::{Color, Piece, Position},
     square::{Rank, Square},
 };
@@ -12,142 +13,182 @@ use crate::{
 use super::simple_move_generator::SimpleMoveGenerator;
 
 pub trait PawnMoveGenerator {
-    fn generate_pawn_moves<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
+    /// Visits single and double pushes landing on `targets`, promoting to each of
+    /// `promotions` on the last rank. Pinned pawns only move along the line through their king.
+    fn generate_pawn_moves<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        targets: Bitboard,
         pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_>;
-
-    fn generate_pawn_attacks<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
+        promotions: &[Piece],
+        visit: &mut F,
+    );
+
+    /// Visits captures landing on `targets`, promoting to each of `promotions` on the last
+    /// rank, and legal en passant captures if `en_passant` is set.
+    fn generate_pawn_attacks<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        targets: Bitboard,
         pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_>;
+        promotions: &[Piece],
+        en_passant: bool,
+        visit: &mut F,
+    );
+
+    /// Whether taking en passant from `from_square` leaves the own king safe. Both pawns leave
+    /// the rank, so pins along it are not seen by `MoveGen::pinned_pieces`.
+    fn is_legal_en_passant(&self, pos: &Position, from_square: Square) -> bool;
 }
 
 impl PawnMoveGenerator for MoveGen {
-    fn generate_pawn_moves<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
+    fn generate_pawn_moves<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        targets: Bitboard,
         pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_> {
+        promotions: &[Piece],
+        visit: &mut F,
+    ) {
         let color = pos.turn;
-        let bb = pos.pieces[color as usize][Piece::Pawn as usize] & !pinned_pieces;
+        let bb = pos.pieces[color as usize][Piece::Pawn as usize];
+        let king_square = pos.pieces[color as usize][Piece::King as usize].msb();
         let forward = match color {
             Color::White => Direction::North,
             Color::Black => Direction::South,
         };
-        let blockers = friendly_occ | enemy_occ;
+        let blockers = pos.occupied;
         let double_push_blockers = blockers | blockers.shift(&forward);
 
-        let iter = bb.into_iter().flat_map(move |from_square| {
-            let single_moves = self.pawn_single_moves(color, from_square) & !blockers;
-            let double_moves = self.pawn_double_moves(color, from_square) & !double_push_blockers;
-
-            single_moves
-                .into_iter()
-                .chain(double_moves.into_iter())
-                .flat_map(move |target_square| {
-                    let promotion_rank = match color {
-                        Color::White => Rank::R8,
-                        Color::Black => Rank::R1,
-                    };
-
-                    if target_square.rank() == promotion_rank {
-                        generate_promotions_vec(from_square, target_square, MoveKind::Promotion)
-                    } else {
-                        vec![Move::new(
-                            from_square,
-                            target_square,
-                            None,
-                            &MoveKind::Quiet,
-                        )]
-                    }
-                    .into_iter()
-                })
-        });
-
-        Box::new(iter)
+        for from_square in bb {
+            let mut allowed = targets;
+            if pinned_pieces.has(from_square) {
+                allowed &= LINE[king_square as usize][from_square as usize];
+            }
+
+            let single_moves = self.pawn_single_moves(color, from_square) & !blockers & allowed;
+            for target_square in single_moves {
+                if target_square.rank() == promotion_rank(color) {
+                    visit_promotions(
+                        from_square,
+                        target_square,
+                        promotions,
+                        &MoveKind::Promotion,
+                        visit,
+                    );
+                } else {
+                    visit(Move::new(
+                        from_square,
+                        target_square,
+                        None,
+                        &MoveKind::Quiet,
+                    ));
+                }
+            }
+
+            let double_moves =
+                self.pawn_double_moves(color, from_square) & !double_push_blockers & allowed;
+            for target_square in double_moves {
+                visit(Move::new(
+                    from_square,
+                    target_square,
+                    None,
+                    &MoveKind::DoublePawnPush,
+                ));
+            }
+        }
     }
 
-    fn generate_pawn_attacks<'a>(
-        &'a self,
-        pos: &'a Position,
-        _friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
+    fn generate_pawn_attacks<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        targets: Bitboard,
         pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_> {
+        promotions: &[Piece],
+        en_passant: bool,
+        visit: &mut F,
+    ) {
         let color = pos.turn;
-        let bb = pos.pieces[color as usize][Piece::Pawn as usize] & !pinned_pieces;
-
-        let iter = bb.into_iter().flat_map(move |from_square| {
-            let attacks = if let Some(en_passant) = pos.en_passant {
-                self.pawn_attacks(color, from_square) & (enemy_occ | en_passant)
-            } else {
-                self.pawn_attacks(color, from_square) & enemy_occ
-            };
-
-            attacks.into_iter().flat_map(move |target_square| {
-                let promotion_rank = match color {
-                    Color::White => Rank::R8,
-                    Color::Black => Rank::R1,
-                };
-
-                if let Some(en_passant) = pos.en_passant {
-                    if target_square == en_passant {
-                        // A hack to check if the move is legal in a very specific, rare occuring
-                        // en-passant case, thus we allow ourselves to clone the position and do more
-                        // calculations.
-                        let mut cloned = pos.clone();
-                        let captured_square = match color {
-                            Color::White => en_passant.bitboard().shift(&Direction::South),
-                            Color::Black => en_passant.bitboard().shift(&Direction::North),
-                        };
-                        cloned.occupied &= !(from_square.bitboard() | captured_square);
-                        if self.is_check(&cloned) {
-                            return vec![].into_iter();
-                        }
-
-                        return vec![Move::new(
-                            from_square,
-                            target_square,
-                            None,
-                            &MoveKind::EnPassant,
-                        )]
-                        .into_iter();
-                    }
+        let bb = pos.pieces[color as usize][Piece::Pawn as usize];
+        let king_square = pos.pieces[color as usize][Piece::King as usize].msb();
+        let enemy_occ = pos.occupation(&color.enemy());
+
+        for from_square in bb {
+            let mut allowed = targets;
+            if pinned_pieces.has(from_square) {
+                allowed &= LINE[king_square as usize][from_square as usize];
+            }
+
+            let attacks = self.pawn_attacks(color, from_square);
+            for target_square in attacks & enemy_occ & allowed {
+                if target_square.rank() == promotion_rank(color) {
+                    visit_promotions(
+                        from_square,
+                        target_square,
+                        promotions,
+                        &MoveKind::PromotionCapture,
+                        visit,
+                    );
+                } else {
+                    visit(Move::new(
+                        from_square,
+                        target_square,
+                        None,
+                        &MoveKind::Capture,
+                    ));
                 }
+            }
+
+            if let Some(en_passant_sq) = pos.en_passant.filter(|_| en_passant) {
+                if attacks.has(en_passant_sq) && self.is_legal_en_passant(pos, from_square) {
+                    visit(Move::new(
+                        from_square,
+                        en_passant_sq,
+                        None,
+                        &MoveKind::EnPassant,
+                    ));
+                }
+            }
+        }
+    }
 
-                if target_square.rank() == promotion_rank {
-                    generate_promotions_vec(from_square, target_square, MoveKind::PromotionCapture)
-                } else {
-                    let kind = MoveKind::Capture;
+    fn is_legal_en_passant(&self, pos: &Position, from_square: Square) -> bool {
+        let Some(en_passant) = pos.en_passant else {
+            return false;
+        };
 
-                    vec![Move::new(from_square, target_square, None, &kind)]
-                }
-                .into_iter()
-            })
-        });
+        let color = pos.turn;
+        let king_square = pos.pieces[color as usize][Piece::King as usize].msb();
+        let captured_square = match color {
+            Color::White => en_passant.bitboard().shift(&Direction::South),
+            Color::Black => en_passant.bitboard().shift(&Direction::North),
+        };
+        let occupied = (pos.occupied & !(from_square.bitboard() | captured_square)) | en_passant;
+
+        // The captured pawn is still in `pos.pieces`, but can no longer give check.
+        (self.attacks_to_square(pos, king_square, color.enemy(), occupied) & !captured_square)
+            .is_empty()
+    }
+}
 
-        Box::new(iter)
+pub const PROMOTIONS: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+pub const UNDERPROMOTIONS: [Piece; 3] = [Piece::Rook, Piece::Bishop, Piece::Knight];
+
+pub fn promotion_rank(color: Color) -> Rank {
+    match color {
+        Color::White => Rank::R8,
+        Color::Black => Rank::R1,
     }
 }
 
-fn generate_promotions_vec(
+fn visit_promotions<F: FnMut(Move)>(
     from_square: Square,
     target_square: Square,
-    kind: MoveKind,
-) -> Vec<Move> {
-    vec![
-        Move::new(from_square, target_square, Some(Piece::Queen), &kind),
-        Move::new(from_square, target_square, Some(Piece::Rook), &kind),
-        Move::new(from_square, target_square, Some(Piece::Bishop), &kind),
-        Move::new(from_square, target_square, Some(Piece::Knight), &kind),
-    ]
+    promotions: &[Piece],
+    kind: &MoveKind,
+    visit: &mut F,
+) {
+    for &promotion in promotions {
+        visit(Move::new(from_square, target_square, Some(promotion), kind));
+    }
 }
@@ -1,6 +1,18 @@
-use sdk::{bitboard::Bitboard, lookup::sliders::Slider, position::Color, square::Square};
+use sdk::{
+    bitboard::Bitboard,
+    lookup::{
+        king::KING_ATTACKS,
+        knights::KNIGHT_ATTACKS,
+        pawns::{PAWN_ATTACKS, PAWN_DOUBLE_MOVES, PAWN_SINGLE_MOVES},
+        sliders::Slider,
+    },
+    position::Color,
+    square::Square,
+};
 
-use crate::{lookup::MagicEntry, generators::movegen::MoveGen};
+#[cfg(feature = "magic")]
+use crate::lookup::MagicEntry;
+use crate::generators::movegen::MoveGen;
 
 pub trait SimpleMoveGenerator {
     fn knight_attacks(&self, square: Square) -> Bitboard;
@@ -16,33 +28,43 @@ pub trait SimpleMoveGenerator {
 
 impl SimpleMoveGenerator for MoveGen {
     fn knight_attacks(&self, square: Square) -> Bitboard {
-        self.lookups.knight_attacks[square as usize]
+        KNIGHT_ATTACKS[square as usize]
     }
 
     fn king_attacks(&self, square: Square) -> Bitboard {
-        self.lookups.king_attacks[square as usize]
+        KING_ATTACKS[square as usize]
     }
 
     fn pawn_attacks(&self, color: Color, square: Square) -> Bitboard {
-        self.lookups.pawn_attacks[color as usize][square as usize]
+        PAWN_ATTACKS[color as usize][square as usize]
     }
 
     fn pawn_single_moves(&self, color: Color, square: Square) -> Bitboard {
-        self.lookups.pawn_single_moves[color as usize][square as usize]
+        PAWN_SINGLE_MOVES[color as usize][square as usize]
     }
 
     fn pawn_double_moves(&self, color: Color, square: Square) -> Bitboard {
-        self.lookups.pawn_double_moves[color as usize][square as usize]
+        PAWN_DOUBLE_MOVES[color as usize][square as usize]
     }
 
     fn rook_moves(&self, square: Square, blockers: Bitboard) -> Bitboard {
-        let entry = self.lookups.rook_magics[square as usize];
-        self.lookups.rook_moves[square as usize][magic_index(&entry, blockers)]
+        #[cfg(feature = "magic")]
+        if let Some(magics) = &self.lookups.magics {
+            let entry = magics.rook_magics[square as usize];
+            return magics.rook_moves[square as usize][magic_index(&entry, blockers)];
+        }
+
+        square.bitboard().rook_attacks(blockers)
     }
 
     fn bishop_moves(&self, square: Square, blockers: Bitboard) -> Bitboard {
-        let entry = self.lookups.bishop_magics[square as usize];
-        self.lookups.bishop_moves[square as usize][magic_index(&entry, blockers)]
+        #[cfg(feature = "magic")]
+        if let Some(magics) = &self.lookups.magics {
+            let entry = magics.bishop_magics[square as usize];
+            return magics.bishop_moves[square as usize][magic_index(&entry, blockers)];
+        }
+
+        square.bitboard().bishop_attacks(blockers)
     }
 
     fn queen_moves(&self, square: Square, blockers: Bitboard) -> Bitboard {
@@ -58,6 +80,7 @@ impl SimpleMoveGenerator for MoveGen {
     }
 }
 
+#[cfg(feature = "magic")]
 fn magic_index(entry: &MagicEntry, blockers: Bitboard) -> usize {
     let blockers = blockers & entry.mask;
     let hash = blockers.0.wrapping_mul(entry.magic);
@@ -1,6 +1,6 @@
 use sdk::{
     bitboard::Bitboard,
-    lookup::sliders::Slider,
+    lookup::{geometry::LINE, sliders::Slider},
     position::{Piece, Position},
 };
 
@@ -12,48 +12,46 @@ use crate::{
 use super::simple_move_generator::SimpleMoveGenerator;
 
 pub trait SliderMoveGenerator {
-    fn generate_slider_moves<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
+    /// Visits moves of the `slider` pieces landing on `targets`. Pinned sliders only move along
+    /// the line through their king.
+    fn generate_slider_moves<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        slider: Slider,
+        targets: Bitboard,
         pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_>;
+        visit: &mut F,
+    );
 }
 
 impl SliderMoveGenerator for MoveGen {
-    fn generate_slider_moves<'a>(
-        &'a self,
-        pos: &'a Position,
-        friendly_occ: Bitboard,
-        enemy_occ: Bitboard,
+    fn generate_slider_moves<F: FnMut(Move)>(
+        &self,
+        pos: &Position,
+        slider: Slider,
+        targets: Bitboard,
         pinned_pieces: Bitboard,
-    ) -> Box<dyn Iterator<Item = Move> + '_> {
-        let iter = [Slider::Bishop, Slider::Rook, Slider::Queen]
-            .into_iter()
-            .flat_map(move |slider| {
-                let piece: Piece = slider.into();
-
-                let bb = pos.pieces[pos.turn as usize][piece as usize] & !pinned_pieces;
-                let blockers = friendly_occ | enemy_occ;
-
-                bb.into_iter().flat_map(move |from_square| {
-                    let attacks = self.slider_moves(slider, from_square, blockers) & !friendly_occ;
-
-                    attacks.into_iter().map(move |target_square| {
-                        let captured_piece = pos.piece_at(&target_square).map(|piece| piece.0);
-
-                        let kind = if captured_piece.is_some() {
-                            MoveKind::Capture
-                        } else {
-                            MoveKind::Quiet
-                        };
-
-                        Move::new(from_square, target_square, None, &kind)
-                    })
-                })
-            });
-
-        Box::new(iter)
+        visit: &mut F,
+    ) {
+        let king_square = pos.pieces[pos.turn as usize][Piece::King as usize].msb();
+        let piece: Piece = slider.into();
+        let bb = pos.pieces[pos.turn as usize][piece as usize];
+
+        for from_square in bb {
+            let mut attacks = self.slider_moves(slider, from_square, pos.occupied) & targets;
+            if pinned_pieces.has(from_square) {
+                attacks &= LINE[king_square as usize][from_square as usize];
+            }
+
+            for target_square in attacks {
+                let kind = if pos.occupied.has(target_square) {
+                    MoveKind::Capture
+                } else {
+                    MoveKind::Quiet
+                };
+
+                visit(Move::new(from_square, target_square, None, &kind));
+            }
+        }
     }
 }
@@ -1,12 +1,10 @@
 #![allow(unused_imports)]
-use std::thread;
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
 #[macro_use]
 extern crate log;
 
-use flexi_logger::{DeferredNow, Logger, WriteMode};
-use log::Record;
-use pretty_env_logger::env_logger::fmt::Style;
 use sdk::{
     bitboard::Bitboard,
     fen::Fen,
@@ -15,7 +13,12 @@ use sdk::{
 };
 use xray::XRayGenerator;
 
+pub mod game;
 pub mod lookup;
+pub mod perft;
+#[cfg(feature = "std")]
+pub mod pgn;
+#[cfg(test)]
 mod tests;
 pub mod utils;
 pub mod xray;
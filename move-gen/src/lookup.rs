@@ -1,30 +1,56 @@
-use std::{io::Read, path::Path};
-
-use sdk::{
-    bitboard::Bitboard,
-    lookup::{
-        in_between::generate_in_between_squares,
-        king::gen_king_attacks,
-        knights::gen_knight_attacks,
-        pawns::{gen_double_pawn_moves, gen_pawn_attacks, gen_single_pawn_moves},
-    },
-};
+#[cfg(feature = "magic")]
+use alloc::{boxed::Box, vec};
+
+#[cfg(feature = "magic")]
+use sdk::bitboard::Bitboard;
 
 use anyhow::Result;
 
-pub struct LookupTables {
+/// Magic tables generated by the `magic` crate, embedded so that no file system is needed.
+/// Only compiled in with the `magic` feature, they add about 2.4MB to the binary.
+#[cfg(feature = "magic")]
+static ROOK_MAGICS: &[u8] = include_bytes!("../../rook_magics.bin");
+#[cfg(feature = "magic")]
+static BISHOP_MAGICS: &[u8] = include_bytes!("../../bishop_magics.bin");
+
+/// Implementation `MoveGen` uses for rook, bishop and queen attacks. Defaults to
+/// [`SliderBackend::Magic`] when the `magic` feature is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SliderBackend {
+    /// Magic bitboards, parsed from the embedded `rook_magics.bin` and `bishop_magics.bin`.
+    #[cfg(feature = "magic")]
+    #[default]
+    Magic,
+    /// Kogge-Stone occluded fills from `sdk::bitboard`. Slower, but needs no attack tables in memory.
+    #[cfg_attr(not(feature = "magic"), default)]
+    KoggeStone,
+}
+
+#[cfg(feature = "magic")]
+/// Attacks of every square, indexed by its magic index.
+pub type AttackTable<const N: usize> = Box<[[Bitboard; N]; 64]>;
+#[cfg(feature = "magic")]
+pub type RookMoves = AttackTable<{ 1 << 12 }>;
+#[cfg(feature = "magic")]
+pub type BishopMoves = AttackTable<{ 1 << 9 }>;
+
+#[cfg(feature = "magic")]
+pub struct MagicTables {
     pub rook_magics: [MagicEntry; 64],
-    pub rook_moves: [[Bitboard; 1 << 12]; 64],
+    pub rook_moves: RookMoves,
     pub bishop_magics: [MagicEntry; 64],
-    pub bishop_moves: [[Bitboard; 1 << 9]; 64],
-    pub knight_attacks: [Bitboard; 64],
-    pub king_attacks: [Bitboard; 64],
-    pub pawn_attacks: [[Bitboard; 64]; 2],
-    pub pawn_single_moves: [[Bitboard; 64]; 2],
-    pub pawn_double_moves: [[Bitboard; 64]; 2],
-    pub in_between: [[Bitboard; 64]; 64],
+    pub bishop_moves: BishopMoves,
 }
 
+/// Tables loaded at runtime. Knight, king and pawn attacks and the in between squares are
+/// computed at compile time, see the statics in `sdk::lookup`.
+pub struct LookupTables {
+    /// Only loaded for [`SliderBackend::Magic`].
+    #[cfg(feature = "magic")]
+    pub magics: Option<MagicTables>,
+}
+
+#[cfg(feature = "magic")]
 #[derive(Clone, Copy)]
 pub struct MagicEntry {
     pub mask: Bitboard,
@@ -32,120 +58,89 @@ pub struct MagicEntry {
     pub index_bits: u8,
 }
 
-pub fn load_lookup_tables() -> Result<LookupTables> {
+pub fn load_lookup_tables(backend: SliderBackend) -> Result<LookupTables> {
+    let tables = match backend {
+        #[cfg(feature = "magic")]
+        SliderBackend::Magic => LookupTables {
+            magics: Some(load_magic_tables()?),
+        },
+        SliderBackend::KoggeStone => LookupTables {
+            #[cfg(feature = "magic")]
+            magics: None,
+        },
+    };
+
+    Ok(tables)
+}
+
+#[cfg(feature = "magic")]
+pub fn load_magic_tables() -> Result<MagicTables> {
     let (rook_magics, rook_moves) = load_rook_magics()
         .map_err(|err| anyhow::format_err!("Couldn't load rook magics: {err:?}"))?;
     let (bishop_magics, bishop_moves) = load_bishop_magics()
         .map_err(|err| anyhow::format_err!("Couldn't load bishop magics: {err:?}"))?;
-    let pawn_single_moves = gen_single_pawn_moves();
-    let pawn_double_moves = gen_double_pawn_moves();
-    let pawn_attacks = gen_pawn_attacks();
-    let knight_attacks = gen_knight_attacks();
-    let king_attacks = gen_king_attacks();
-    let in_between = generate_in_between_squares();
-
-    Ok(LookupTables {
+
+    Ok(MagicTables {
         rook_magics,
         rook_moves,
         bishop_magics,
         bishop_moves,
-        knight_attacks,
-        king_attacks,
-        pawn_attacks,
-        pawn_single_moves,
-        pawn_double_moves,
-        in_between,
     })
 }
 
-pub fn load_rook_magics() -> Result<([MagicEntry; 64], [[Bitboard; 1 << 12]; 64])> {
-    let mut magics = [MagicEntry {
-        mask: Bitboard(0),
-        magic: 0,
-        index_bits: 0,
-    }; 64];
-
-    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .unwrap()
-        .join("rook_magics.bin");
-
-    let mut file = std::fs::File::open(path)?;
-
-    for magic in magics.iter_mut() {
-        let mut mask_bytes = [0u8; 8];
-        file.read_exact(&mut mask_bytes)?;
-        let mask = Bitboard(u64::from_be_bytes(mask_bytes));
-
-        let mut magic_bytes = [0u8; 8];
-        file.read_exact(&mut magic_bytes)?;
-        let magic_number = u64::from_be_bytes(magic_bytes);
-
-        let mut index_bits_bytes = [0u8; 1];
-        file.read_exact(&mut index_bits_bytes)?;
-        let index_bits = u8::from_be_bytes(index_bits_bytes);
-
-        magic.mask = mask;
-        magic.magic = magic_number;
-        magic.index_bits = index_bits;
-    }
+#[cfg(feature = "magic")]
+/// Allocates the attack table directly on the heap, it doesn't fit on the default stack.
+fn empty_moves<const N: usize>() -> AttackTable<N> {
+    vec![[Bitboard(0); N]; 64]
+        .into_boxed_slice()
+        .try_into()
+        .expect("BUG: Table has 64 entries")
+}
 
-    let mut buffer: Vec<u8> = Vec::new();
-    file.read_to_end(&mut buffer)?;
+#[cfg(feature = "magic")]
+pub fn load_rook_magics() -> Result<([MagicEntry; 64], RookMoves)> {
+    parse_magics(ROOK_MAGICS)
+}
 
-    let mut moves = [[Bitboard(0); 1 << 12]; 64];
+#[cfg(feature = "magic")]
+pub fn load_bishop_magics() -> Result<([MagicEntry; 64], BishopMoves)> {
+    parse_magics(BISHOP_MAGICS)
+}
 
-    let size = 1 << 12;
+#[cfg(feature = "magic")]
+/// Reads 64 entries of big endian mask, magic number and index bits, followed by the attack
+/// table with `N` big endian bitboards per square.
+fn parse_magics<const N: usize>(bytes: &[u8]) -> Result<([MagicEntry; 64], AttackTable<N>)> {
+    const ENTRY_SIZE: usize = 8 + 8 + 1;
 
-    for (idx, elem) in buffer.chunks_exact(8).enumerate() {
-        moves[idx / size][idx % size] = Bitboard(u64::from_be_bytes(elem.try_into().unwrap()));
-    }
+    let (entries, table) = bytes
+        .split_at_checked(64 * ENTRY_SIZE)
+        .ok_or_else(|| anyhow::format_err!("Magic entries are truncated"))?;
 
-    Ok((magics, moves))
-}
-
-pub fn load_bishop_magics() -> Result<([MagicEntry; 64], [[Bitboard; 1 << 9]; 64])> {
     let mut magics = [MagicEntry {
         mask: Bitboard(0),
         magic: 0,
         index_bits: 0,
     }; 64];
 
-    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .unwrap()
-        .join("bishop_magics.bin");
-
-    let mut file = std::fs::File::open(path)?;
-
-    for magic in magics.iter_mut() {
-        let mut mask_bytes = [0u8; 8];
-        file.read_exact(&mut mask_bytes)?;
-        let mask = Bitboard(u64::from_be_bytes(mask_bytes));
-
-        let mut magic_bytes = [0u8; 8];
-        file.read_exact(&mut magic_bytes)?;
-        let magic_number = u64::from_be_bytes(magic_bytes);
-
-        let mut index_bits_bytes = [0u8; 1];
-        file.read_exact(&mut index_bits_bytes)?;
-        let index_bits = u8::from_be_bytes(index_bits_bytes);
-
-        magic.mask = mask;
-        magic.magic = magic_number;
-        magic.index_bits = index_bits;
+    for (magic, entry) in magics.iter_mut().zip(entries.chunks_exact(ENTRY_SIZE)) {
+        magic.mask = Bitboard(u64::from_be_bytes(entry[0..8].try_into().unwrap()));
+        magic.magic = u64::from_be_bytes(entry[8..16].try_into().unwrap());
+        magic.index_bits = entry[16];
     }
 
-    let mut buffer: Vec<u8> = Vec::new();
-
-    file.read_to_end(&mut buffer)?;
-
-    let size = 1 << 9;
+    if table.len() != 64 * N * 8 {
+        anyhow::bail!(
+            "Attack table has {} bytes, expected {}",
+            table.len(),
+            64 * N * 8
+        );
+    }
 
-    let mut moves = [[Bitboard(0); 1 << 9]; 64];
+    let mut moves = empty_moves::<N>();
 
-    for (idx, elem) in buffer.chunks_exact(8).enumerate() {
-        moves[idx / size][idx % size] = Bitboard(u64::from_be_bytes(elem.try_into().unwrap()));
+    for (idx, elem) in table.chunks_exact(8).enumerate() {
+        moves[idx / N][idx % N] = Bitboard(u64::from_be_bytes(elem.try_into().unwrap()));
     }
 
     Ok((magics, moves))
@@ -0,0 +1,124 @@
+use core::ops::Deref;
+
+use super::Move;
+
+/// Capacity of a [`MoveList`], more than the most moves any legal position has.
+pub const MAX_MOVES: usize = 256;
+
+/// Moves of a position stored inline, so it can live on the stack of a search or perft.
+///
+/// Each move has a score, zero when pushed, for move ordering. Reordering with
+/// [`MoveList::swap`] keeps moves and scores together.
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    scores: [i32; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub const fn new() -> Self {
+        const NULL: Move = Move::null();
+
+        MoveList {
+            moves: [NULL; MAX_MOVES],
+            scores: [0; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        self.push_scored(mv, 0);
+    }
+
+    pub fn push_scored(&mut self, mv: Move, score: i32) {
+        assert!(self.len < MAX_MOVES, "BUG: Move list is full");
+
+        self.moves[self.len] = mv;
+        self.scores[self.len] = score;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn scores(&self) -> &[i32] {
+        &self.scores[..self.len]
+    }
+
+    pub fn scores_mut(&mut self) -> &mut [i32] {
+        &mut self.scores[..self.len]
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.moves[..self.len].swap(a, b);
+        self.scores[..self.len].swap(a, b);
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &Self::Target {
+        &self.moves[..self.len]
+    }
+}
+
+impl core::fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = core::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            list: self,
+            next: 0,
+        }
+    }
+}
+
+/// Owning iterator over the moves of a [`MoveList`].
+pub struct IntoIter {
+    list: MoveList,
+    next: usize,
+}
+
+impl Iterator for IntoIter {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mv = self.list.get(self.next)?.clone();
+        self.next += 1;
+
+        Some(mv)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.list.len - self.next;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
@@ -1,21 +1,45 @@
-use core::fmt;
-use std::{collections::HashSet, fmt::Formatter};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{
+    fmt::{self, Formatter},
+    str::FromStr,
+};
 
+#[cfg(feature = "std")]
+use sdk::svg::Arrow;
 use sdk::{
-    position::{CastlingKind, Color, Piece, Position},
-    square::{File, Square},
+    lookup::pawns::PAWN_ATTACKS,
+    position::{Castling, CastlingKind, Color, Piece, Position},
+    square::{File, Rank, Square},
 };
 
-type Result<T> = std::result::Result<T, anyhow::Error>;
+pub mod list;
+
+type Result<T> = core::result::Result<T, anyhow::Error>;
 
 #[derive(Clone, Hash, Eq, PartialEq)]
 pub struct Move {
     inner: u16,
 }
 
+/// State a move overwrites, returned by [`MakeMove::make_move`] to undo the move exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateInfo {
+    pub castling: Castling,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u16,
+    /// Piece the move captured.
+    pub captured: Option<Piece>,
+    /// Zobrist key of the position before the move.
+    pub hash: u64,
+}
+
 pub trait MakeMove {
-    fn make_move(&mut self, mv: &Move) -> Result<Option<Piece>>;
-    fn undo_move(&mut self, mv: &Move, captured: Option<Piece>) -> Result<()>;
+    fn make_move(&mut self, mv: &Move) -> Result<StateInfo>;
+    /// Takes back `mv`, restoring the position it was made in from `state`.
+    fn undo_move(&mut self, mv: &Move, state: &StateInfo) -> Result<()>;
     fn validate_move(&self, mv: &Move) -> Result<()>;
 }
 
@@ -44,33 +68,41 @@ impl fmt::Display for Move {
 }
 
 impl MakeMove for Position {
-    fn make_move(&mut self, mv: &Move) -> Result<Option<Piece>> {
+    fn make_move(&mut self, mv: &Move) -> Result<StateInfo> {
         let from = mv.from();
         let to = mv.to();
         let color = self.turn;
+        let state = StateInfo {
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            captured: None,
+            hash: self.hash,
+        };
 
-        for (rook_sq, kind) in [
-            (Square::A1, CastlingKind::WhiteQueenside),
-            (Square::H1, CastlingKind::WhiteKingside),
-            (Square::A8, CastlingKind::BlackQueenside),
-            (Square::H8, CastlingKind::BlackKingside),
-        ]
-        .iter()
-        {
-            if mv.from() == *rook_sq || mv.to() == *rook_sq {
-                self.castling.remove_castling_kind(kind);
+        let (from_piece, from_color) = self.piece_at(&from).expect("BUG: No piece at from square");
+
+        let mut castling = self.castling;
+        for kind in CastlingKind::all() {
+            if castling
+                .rook_square(&kind)
+                .is_some_and(|rook_sq| from == rook_sq || to == rook_sq)
+            {
+                castling.remove_castling_kind(&kind);
             }
         }
-
-        let (from_piece, from_color) = self
-            .remove_piece_at(&from)
-            .expect("BUG: No piece at from square");
+        // Covers castling too, which is encoded as a king move.
+        if from_piece == Piece::King {
+            castling.remove_color_castling(&color);
+        }
+        self.set_castling(castling);
+        self.set_en_passant(None);
 
         let captured = match mv.kind() {
             MoveKind::Capture | MoveKind::Quiet => {
                 let captured = self.remove_piece_at(&to);
 
-                self.add_piece_at(to, from_piece, from_color)?;
+                self.move_piece(from, to)?;
 
                 captured
             }
@@ -84,7 +116,7 @@ impl MakeMove for Position {
                     .remove_piece_at(&captured_sq)
                     .expect("BUG: No piece at to square");
 
-                self.add_piece_at(to, from_piece, from_color)?;
+                self.move_piece(from, to)?;
 
                 Some(captured)
             }
@@ -93,15 +125,15 @@ impl MakeMove for Position {
                     .castling_kind(&self.turn)
                     .expect("BUG: Move does not castle.");
 
-                let (rook_from, _) = castling.from_squares();
                 let (rook_to, king_to) = castling.target_squares();
 
-                let (rook, _) = self
-                    .remove_piece_at(&rook_from)
-                    .expect("BUG: No piece at rook from square");
-
-                self.add_piece_at(king_to, from_piece, from_color)?;
-                self.add_piece_at(rook_to, rook, from_color)?;
+                // Castling is encoded as king takes rook. Both are lifted before landing, as in
+                // Chess960 either may land on the square the other one left.
+                self.remove_piece_at(&to)
+                    .expect("BUG: No rook to castle with");
+                self.remove_piece_at(&from);
+                self.add_piece_at(king_to, Piece::King, color)?;
+                self.add_piece_at(rook_to, Piece::Rook, color)?;
 
                 None
             }
@@ -109,29 +141,30 @@ impl MakeMove for Position {
                 let promotion = mv.promotion().expect("BUG: No promotion piece");
                 let captured = self.remove_piece_at(&to);
 
+                self.remove_piece_at(&from);
                 self.add_piece_at(to, promotion, from_color)?;
 
                 captured
             }
             MoveKind::DoublePawnPush => {
-                let captured = self.remove_piece_at(&to);
+                self.move_piece(from, to)?;
 
-                self.add_piece_at(to, from_piece, from_color)?;
-
-                let enpass_sq = mv
-                    .to()
-                    .offset(0, if color == Color::White { -1 } else { 1 })
+                let enpass_sq = to
+                    .offset(if color == Color::White { -1 } else { 1 }, 0)
                     .expect("BUG: Invalid en passant square");
 
-                self.en_passant = Some(enpass_sq);
+                // Only set when an enemy pawn can take, so that positions differing in an en
+                // passant square nobody can use hash the same.
+                let enemy_pawns = self.pieces[color.enemy() as usize][Piece::Pawn as usize];
+                if !(PAWN_ATTACKS[color as usize][enpass_sq as usize] & enemy_pawns).is_empty() {
+                    self.set_en_passant(Some(enpass_sq));
+                }
 
-                captured
+                None
             }
         }
         .map(|(piece, _)| piece);
 
-        self.occupied = self.occupation(&Color::White) | self.occupation(&Color::Black);
-        self.en_passant = None;
         self.halfmove_clock = if captured.is_some() || from_piece == Piece::Pawn {
             0
         } else {
@@ -142,103 +175,60 @@ impl MakeMove for Position {
             self.fullmove_number += 1;
         }
 
-        Ok(captured)
+        Ok(StateInfo { captured, ..state })
     }
 
-    fn undo_move(&mut self, mv: &Move, captured: Option<Piece>) -> Result<()> {
+    fn undo_move(&mut self, mv: &Move, state: &StateInfo) -> Result<()> {
         let from = mv.from();
         let to = mv.to();
+        let color = self.enemy();
 
         match mv.kind() {
-            MoveKind::Quiet | MoveKind::DoublePawnPush => {
-                let (piece, color) = self
-                    .remove_piece_at(&to)
-                    .expect("BUG: No piece at to square");
-
-                self.add_piece_at(from, piece, color)?;
-            }
-            MoveKind::Capture => {
-                let (piece, color) = self
-                    .remove_piece_at(&to)
-                    .expect("BUG: No piece at to square");
-
-                self.add_piece_at(from, piece, color)?;
-
-                let captured_color = self.turn;
-                let captured_piece = captured.expect("BUG: No captured piece in Capture move");
-
-                self.add_piece_at(to, captured_piece, captured_color)?;
-            }
-            MoveKind::EnPassant => {
-                let (piece, color) = self
-                    .remove_piece_at(&to)
-                    .expect("BUG: No piece at to square");
-
-                self.add_piece_at(from, piece, color)?;
-
-                let captured_color = self.turn;
-                let captured_piece = captured.expect("BUG: No captured piece in EnPassant move");
-
-                let enpass_sq = mv
-                    .to()
-                    .offset(
-                        0,
-                        if captured_color == Color::White {
-                            -8
-                        } else {
-                            8
-                        },
-                    )
-                    .expect("BUG: Invalid en passant square");
-
-                self.add_piece_at(enpass_sq, captured_piece, captured_color)?;
+            MoveKind::Quiet
+            | MoveKind::DoublePawnPush
+            | MoveKind::Capture
+            | MoveKind::EnPassant => {
+                self.move_piece(to, from)?;
             }
             MoveKind::Castling => {
-                let castling = CastlingKind::from(to);
+                let castling = mv
+                    .castling_kind(&color)
+                    .expect("BUG: Move does not castle.");
                 let (rook_to, king_to) = castling.target_squares();
-                let (rook_from, king_from) = castling.from_squares();
-
-                let (rook, rook_color) = self
-                    .remove_piece_at(&rook_to)
-                    .expect("BUG: No piece at rook square");
-
-                let (king, color) = self
-                    .remove_piece_at(&king_to)
-                    .expect("BUG: No piece at king square");
-
-                self.add_piece_at(rook_from, rook, rook_color)?;
-                self.add_piece_at(king_from, king, color)?;
-
-                self.castling.add_castling_kind(&castling);
-            }
-            MoveKind::Promotion => {
-                let (piece, color) = self
-                    .remove_piece_at(&to)
-                    .expect("BUG: No piece at to square");
 
-                self.add_piece_at(from, piece, color)?;
+                self.remove_piece_at(&rook_to);
+                self.remove_piece_at(&king_to);
+                self.add_piece_at(from, Piece::King, color)?;
+                self.add_piece_at(to, Piece::Rook, color)?;
             }
-            MoveKind::PromotionCapture => {
-                let (_, color) = self
-                    .remove_piece_at(&to)
-                    .expect("BUG: No piece at to square");
-
+            MoveKind::Promotion | MoveKind::PromotionCapture => {
+                self.remove_piece_at(&to)
+                    .expect("BUG: No promoted piece at to square");
                 self.add_piece_at(from, Piece::Pawn, color)?;
+            }
+        }
 
-                let captured_color = self.turn;
-                let captured_piece =
-                    captured.expect("BUG: No captured piece in PromotionCapture move");
+        if let Some(captured) = state.captured {
+            let captured_sq = if mv.is_enpass_capture() {
+                to.offset(if color == Color::White { -1 } else { 1 }, 0)
+                    .expect("BUG: Invalid en passant square")
+            } else {
+                to
+            };
 
-                self.add_piece_at(to, captured_piece, captured_color)?;
-            }
+            self.add_piece_at(captured_sq, captured, self.turn)?;
         }
 
-        self.turn = self.swap_turn();
+        self.turn = self.enemy();
         if self.turn == Color::Black {
             self.fullmove_number -= 1;
         }
 
-        self.halfmove_clock -= 1;
+        // Set directly, the saved hash already covers castling rights and en passant.
+        self.castling = state.castling;
+        self.en_passant = state.en_passant;
+        self.halfmove_clock = state.halfmove_clock;
+        self.hash = state.hash;
 
         Ok(())
     }
@@ -256,7 +246,8 @@ impl MakeMove for Position {
         }
 
         if let Some((_, to_color)) = self.piece_at(&mv.to()) {
-            if from_color == to_color {
+            // Castling is encoded as the king taking its own rook.
+            if from_color == to_color && !matches!(mv.kind(), MoveKind::Castling) {
                 return Err(anyhow::anyhow!(
                     "Cannot capture piece of same color: {}",
                     mv.to()
@@ -297,25 +288,89 @@ impl Move {
             MoveKind::EnPassant => {
                 mv.set_enpass_capture();
             }
+            // Castling is encoded as king takes rook, which also covers Chess960.
             MoveKind::Castling => {
-                if to.file() == File::C {
-                    mv.set_queen_castle();
-                } else if to.file() == File::G {
+                if to.file() > from.file() {
                     mv.set_king_castle();
+                } else {
+                    mv.set_queen_castle();
                 }
             }
+            MoveKind::DoublePawnPush => {
+                mv.set_double_pawn_push();
+            }
             MoveKind::Promotion => {
                 mv.set_promotion(promotion.expect("BUG: No promotion piece"));
             }
             MoveKind::PromotionCapture => {
                 mv.set_promotion_capture(promotion.expect("BUG: No promotion piece"));
             }
-            _ => {}
+            MoveKind::Quiet => {}
         }
 
         mv
     }
 
+    /// Parses a move in UCI form, e.g. `e2e4` or `e7e8q`. The result only carries the squares and
+    /// the promotion piece, use `ChessNotation::parse_uci` to get the move with all its flags.
+    pub fn from_uci(uci: &str) -> Result<Move> {
+        let invalid = || anyhow::anyhow!("Invalid UCI move: '{uci}'");
+
+        let from = uci.get(0..2).ok_or_else(invalid)?;
+        let to = uci.get(2..4).ok_or_else(invalid)?;
+        let from = Square::from_str(from).map_err(|_| invalid())?;
+        let to = Square::from_str(to).map_err(|_| invalid())?;
+        if from == to {
+            return Err(invalid());
+        }
+
+        match uci.get(4..).ok_or_else(invalid)? {
+            "" => Ok(Move::new(from, to, None, &MoveKind::Quiet)),
+            promotion => {
+                let piece = Piece::from_str(promotion).map_err(|_| invalid())?;
+                if matches!(piece, Piece::Pawn | Piece::King)
+                    || !matches!(to.rank(), Rank::R1 | Rank::R8)
+                {
+                    return Err(invalid());
+                }
+
+                Ok(Move::new(from, to, Some(piece), &MoveKind::Promotion))
+            }
+        }
+    }
+
+    /// Formats the move in UCI form, e.g. `e2e4` or `e7e8q`. Castling is written as the king
+    /// move, e.g. `e1g1`, use [`Move::to_uci_chess960`] in Chess960 games.
+    pub fn to_uci(&self) -> String {
+        match self.promotion() {
+            Some(promotion) => format!("{}{}{}", self.from(), self.target(), promotion),
+            None => format!("{}{}", self.from(), self.target()),
+        }
+    }
+
+    /// Formats the move in UCI form with castling written as king takes rook, e.g. `e1h1`, as
+    /// expected by GUIs with `UCI_Chess960` enabled.
+    pub fn to_uci_chess960(&self) -> String {
+        match self.promotion() {
+            Some(promotion) => format!("{}{}{}", self.from(), self.to(), promotion),
+            None => format!("{}{}", self.from(), self.to()),
+        }
+    }
+
+    /// Square the moving piece lands on. Same as [`Move::to`] except for castling, which is
+    /// encoded as king takes rook, where it is the target of the king.
+    pub fn target(&self) -> Square {
+        let file = if self.is_king_castle() {
+            File::G
+        } else if self.is_queen_castle() {
+            File::C
+        } else {
+            return self.to();
+        };
+
+        Square::from((file, self.from().rank()))
+    }
+
     pub fn from(&self) -> Square {
         Square::try_from((self.inner & 0b0000000000111111) as u8).expect("Invalid square")
     }
@@ -367,7 +422,7 @@ impl Move {
     }
 
     pub fn is_double_pawn_push(&self) -> bool {
-        self.inner & 0b1111000000000000 != 0
+        self.inner & 0b1111000000000000 == 0b0001000000000000
     }
 
     pub fn is_king_castle(&self) -> bool {
@@ -416,6 +471,10 @@ impl Move {
         }
     }
 
+    fn set_double_pawn_push(&mut self) {
+        self.inner |= 0b0001000000000000;
+    }
+
     fn set_capture(&mut self) {
         self.inner |= 0b0100000000000000;
     }
@@ -437,3 +496,41 @@ impl Move {
         self.inner |= 0b0011000000000000;
     }
 }
+
+/// Arrow from the moving piece to its target, castling points to where the king lands.
+#[cfg(feature = "std")]
+impl From<&Move> for Arrow {
+    fn from(mv: &Move) -> Self {
+        Arrow::new(mv.from(), mv.target())
+    }
+}
+
+/// Moves are serialized in UCI form, see [`Move::to_uci`]. Chess960 castling that leaves the king
+/// on its square is written as king takes rook instead, e.g. `g1h1` rather than `g1g1`.
+///
+/// Deserializing goes through [`Move::from_uci`], so strings that are not a valid UCI move are
+/// rejected, and capture, castling and en passant flags are not restored. Pass the string to
+/// `ChessNotation::parse_uci` with the position to get them back.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Move {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        if self.from() == self.target() {
+            serializer.serialize_str(&self.to_uci_chess960())
+        } else {
+            serializer.serialize_str(&self.to_uci())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Move {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        let uci = String::deserialize(deserializer)?;
+        Move::from_uci(&uci).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,268 @@
+use alloc::vec::Vec;
+use core::ops::AddAssign;
+
+use sdk::{
+    fen::Fen,
+    position::{Piece, Position},
+};
+
+use crate::{
+    generators::movegen::MoveGen,
+    r#move::{list::MoveList, MakeMove, Move, MoveKind},
+};
+
+/// Number of leaf nodes of the tree of legal moves `depth` plies deep. The last ply is counted
+/// in bulk, without making its moves.
+pub fn perft(move_gen: &MoveGen, pos: &Position, depth: usize) -> u64 {
+    let mut pos = pos.clone();
+
+    count_nodes(move_gen, &mut pos, depth)
+}
+
+/// [`perft`] of each legal move of `pos`, in generation order.
+pub fn divide(move_gen: &MoveGen, pos: &Position, depth: usize) -> Vec<(Move, u64)> {
+    assert!(depth > 0, "Can't divide perft at depth 0");
+
+    let mut pos = pos.clone();
+    let mut moves = MoveList::new();
+    move_gen.generate_legal_moves_into(&pos, &mut moves);
+
+    moves
+        .into_iter()
+        .map(|mv| {
+            let state = pos
+                .make_move(&mv)
+                .expect("BUG: Generated move not playable");
+            let nodes = count_nodes(move_gen, &mut pos, depth - 1);
+            pos.undo_move(&mv, &state)
+                .expect("BUG: Played move not undoable");
+
+            (mv, nodes)
+        })
+        .collect()
+}
+
+fn count_nodes(move_gen: &MoveGen, pos: &mut Position, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut moves = MoveList::new();
+    move_gen.generate_legal_moves_into(pos, &mut moves);
+
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for mv in &moves {
+        let state = pos.make_move(mv).expect("BUG: Generated move not playable");
+        nodes += count_nodes(move_gen, pos, depth - 1);
+        pos.undo_move(mv, &state)
+            .expect("BUG: Played move not undoable");
+    }
+
+    nodes
+}
+
+/// Leaf nodes of a perft tree and the moves leading to them, by kind. Captures include en
+/// passant, discovered and double checks are also counted as checks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passant: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    /// Checks given by a piece other than the one that moved.
+    pub discovered_checks: u64,
+    pub double_checks: u64,
+    pub checkmates: u64,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passant += other.en_passant;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.discovered_checks += other.discovered_checks;
+        self.double_checks += other.double_checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+/// [`perft`] with the leaf nodes counted by kind. Every leaf is played, so this is much slower.
+pub fn perft_detailed(move_gen: &MoveGen, pos: &Position, depth: usize) -> PerftStats {
+    let mut pos = pos.clone();
+
+    count_stats(move_gen, &mut pos, depth)
+}
+
+fn count_stats(move_gen: &MoveGen, pos: &mut Position, depth: usize) -> PerftStats {
+    if depth == 0 {
+        return PerftStats {
+            nodes: 1,
+            ..PerftStats::default()
+        };
+    }
+
+    let mut moves = MoveList::new();
+    move_gen.generate_legal_moves_into(pos, &mut moves);
+
+    let mut stats = PerftStats::default();
+    for mv in &moves {
+        let state = pos.make_move(mv).expect("BUG: Generated move not playable");
+        if depth == 1 {
+            stats += leaf_stats(move_gen, pos, mv);
+        } else {
+            stats += count_stats(move_gen, pos, depth - 1);
+        }
+        pos.undo_move(mv, &state)
+            .expect("BUG: Played move not undoable");
+    }
+
+    stats
+}
+
+/// Stats of the leaf reached by `mv`, already played in `pos`.
+fn leaf_stats(move_gen: &MoveGen, pos: &Position, mv: &Move) -> PerftStats {
+    let mut stats = PerftStats {
+        nodes: 1,
+        ..PerftStats::default()
+    };
+
+    match mv.kind() {
+        MoveKind::Capture => stats.captures = 1,
+        MoveKind::EnPassant => {
+            stats.captures = 1;
+            stats.en_passant = 1;
+        }
+        MoveKind::Castling => stats.castles = 1,
+        MoveKind::Promotion => stats.promotions = 1,
+        MoveKind::PromotionCapture => {
+            stats.captures = 1;
+            stats.promotions = 1;
+        }
+        MoveKind::Quiet | MoveKind::DoublePawnPush => {}
+    }
+
+    let king_square = pos.pieces[pos.turn as usize][Piece::King as usize].msb();
+    let checkers = move_gen.attacks_to_square(pos, king_square, pos.enemy(), pos.occupied);
+    if checkers.is_empty() {
+        return stats;
+    }
+
+    // The moved piece checks from where it landed, for castling that is the rook.
+    let landed = match mv.castling_kind(&pos.enemy()) {
+        Some(castling) => castling.target_squares().0,
+        None => mv.to(),
+    };
+
+    stats.checks = 1;
+    // A double check is only counted as such, even when one of the checks is discovered.
+    stats.discovered_checks = u64::from(checkers.count() == 1 && !checkers.has(landed));
+    stats.double_checks = u64::from(checkers.count() > 1);
+    stats.checkmates = u64::from(move_gen.generate_legal_moves(pos).next().is_none());
+
+    stats
+}
+
+/// A position with known perft node counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftCase {
+    pub name: &'static str,
+    pub fen: &'static str,
+    /// Node counts from depth 1 on.
+    pub nodes: &'static [u64],
+}
+
+/// Standard perft positions, see <https://www.chessprogramming.org/Perft_Results>.
+pub const PERFT_SUITE: &[PerftCase] = &[
+    PerftCase {
+        name: "Start position",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        nodes: &[20, 400, 8_902, 197_281, 4_865_609, 119_060_324],
+    },
+    PerftCase {
+        name: "Kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        nodes: &[48, 2_039, 97_862, 4_085_603, 193_690_690],
+    },
+    PerftCase {
+        name: "Position 3",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        nodes: &[14, 191, 2_812, 43_238, 674_624, 11_030_083],
+    },
+    PerftCase {
+        name: "Position 4",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        nodes: &[6, 264, 9_467, 422_333, 15_833_292],
+    },
+    PerftCase {
+        name: "Position 4 mirrored",
+        fen: "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        nodes: &[6, 264, 9_467, 422_333, 15_833_292],
+    },
+    PerftCase {
+        name: "Position 5",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        nodes: &[44, 1_486, 62_379, 2_103_487, 89_941_194],
+    },
+    PerftCase {
+        name: "Position 6",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        nodes: &[46, 2_079, 89_890, 3_894_594, 164_075_551],
+    },
+    PerftCase {
+        name: "Chess960 position 1",
+        fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        nodes: &[21, 528, 12_189, 326_672, 8_146_062],
+    },
+];
+
+/// Perft of a [`PerftCase`] at one depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftResult {
+    pub case: &'static PerftCase,
+    pub depth: usize,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl PerftResult {
+    pub fn passed(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+/// Runs the cases of `suite` at every depth with at most `max_nodes` expected nodes.
+pub fn run_suite(
+    move_gen: &MoveGen,
+    suite: &'static [PerftCase],
+    max_nodes: u64,
+) -> Vec<PerftResult> {
+    let mut results = Vec::new();
+
+    for case in suite {
+        let pos = Position::from_fen(case.fen.into()).expect("BUG: Invalid perft suite FEN");
+
+        for (depth, &expected) in (1..).zip(case.nodes) {
+            if expected > max_nodes {
+                break;
+            }
+
+            results.push(PerftResult {
+                case,
+                depth,
+                expected,
+                actual: perft(move_gen, &pos, depth),
+            });
+        }
+    }
+
+    results
+}
@@ -0,0 +1,844 @@
+//! Portable Game Notation. [`PgnReader`] streams games out of any `BufRead`, one game in memory
+//! at a time, and replays every move through [`MoveGen`] so illegal games are rejected.
+//! [`PgnGame`] is written back with `Display` or [`PgnWriter`]. Its main line is a [`Game`],
+//! so games read from PGN and games played elsewhere share one move history.
+
+use std::{
+    fmt::{self, Display, Write as _},
+    io::{self, BufRead, Write},
+    str::FromStr,
+    time::Duration,
+};
+
+use sdk::{
+    fen::{Fen, FenError},
+    position::{Color, Position},
+};
+
+use crate::{
+    game::Game,
+    generators::movegen::MoveGen,
+    r#move::{MakeMove, Move},
+    utils::chess_notation::ChessNotation,
+};
+
+/// Maximum line length of written movetext, as recommended by the PGN standard.
+const LINE_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PgnResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// Game still in progress, abandoned or result unknown, written as `*`.
+    #[default]
+    Unknown,
+}
+
+/// Evaluation from a `[%eval]` comment command, from white's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgnEval {
+    Centipawns(i32),
+    /// Mate in the given number of moves, negative if black mates.
+    Mate(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    pub mv: Move,
+    pub san: String,
+    /// Numeric annotation glyphs, `!` and `?` style suffixes are stored as their NAGs 1 to 6.
+    pub nags: Vec<u8>,
+    /// Comments between the previous move and this one, e.g. at the start of a variation.
+    pub comments_before: Vec<String>,
+    /// Comments after the move, without `[%clk]` and `[%eval]` commands.
+    pub comments: Vec<String>,
+    /// Clock time left after the move, from `[%clk]`.
+    pub clock: Option<Duration>,
+    pub eval: Option<PgnEval>,
+    /// Alternatives to this move, each played from the position before it.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    /// Tag pairs in the order they were read.
+    pub tags: Vec<(String, String)>,
+    /// Main line, starting from the `FEN` tag or the standard starting position.
+    game: Game,
+    /// Annotated main line, one entry per move of `game`.
+    moves: Vec<PgnMove>,
+    /// Game termination marker of the movetext.
+    pub result: PgnResult,
+}
+
+#[derive(Debug)]
+pub enum PgnError {
+    Io(io::Error),
+    InvalidTag(String),
+    InvalidFen(FenError),
+    IllegalMove { san: String, fen: String },
+    UnexpectedToken(String),
+    UnterminatedComment,
+    UnbalancedVariation,
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::Io(err) => write!(f, "{err}"),
+            PgnError::InvalidTag(line) => write!(f, "Invalid tag pair: '{line}'"),
+            PgnError::InvalidFen(err) => write!(f, "Invalid FEN tag: {err}"),
+            PgnError::IllegalMove { san, fen } => {
+                write!(f, "Illegal or ambiguous move '{san}' in {fen}")
+            }
+            PgnError::UnexpectedToken(token) => write!(f, "Unexpected token '{token}'"),
+            PgnError::UnterminatedComment => write!(f, "Unterminated comment"),
+            PgnError::UnbalancedVariation => write!(f, "Unbalanced variation parentheses"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<io::Error> for PgnError {
+    fn from(err: io::Error) -> Self {
+        PgnError::Io(err)
+    }
+}
+
+impl Display for PgnResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnResult::WhiteWins => write!(f, "1-0"),
+            PgnResult::BlackWins => write!(f, "0-1"),
+            PgnResult::Draw => write!(f, "1/2-1/2"),
+            PgnResult::Unknown => write!(f, "*"),
+        }
+    }
+}
+
+impl FromStr for PgnResult {
+    type Err = PgnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(PgnResult::WhiteWins),
+            "0-1" => Ok(PgnResult::BlackWins),
+            "1/2-1/2" => Ok(PgnResult::Draw),
+            "*" => Ok(PgnResult::Unknown),
+            _ => Err(PgnError::UnexpectedToken(s.to_string())),
+        }
+    }
+}
+
+impl Display for PgnEval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnEval::Centipawns(cp) => {
+                let sign = if *cp < 0 { "-" } else { "" };
+                write!(f, "{sign}{}.{:02}", cp.abs() / 100, cp.abs() % 100)
+            }
+            PgnEval::Mate(moves) => write!(f, "#{moves}"),
+        }
+    }
+}
+
+impl FromStr for PgnEval {
+    type Err = PgnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PgnError::UnexpectedToken(s.to_string());
+
+        if let Some(moves) = s.strip_prefix('#') {
+            return moves.parse().map(PgnEval::Mate).map_err(|_| invalid());
+        }
+
+        let pawns = s.parse::<f64>().map_err(|_| invalid())?;
+        if !pawns.is_finite() || pawns.abs() > f64::from(i32::MAX / 100) {
+            return Err(invalid());
+        }
+        Ok(PgnEval::Centipawns((pawns * 100.0).round() as i32))
+    }
+}
+
+fn format_clock(clock: Duration) -> String {
+    let secs = clock.as_secs();
+    let tenths = clock.subsec_millis() / 100;
+    let mut formatted = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    if tenths != 0 {
+        write!(formatted, ".{tenths}").unwrap();
+    }
+
+    formatted
+}
+
+fn parse_clock(s: &str) -> Option<Duration> {
+    let mut secs = 0.0;
+    for part in s.split(':') {
+        secs = secs * 60.0 + part.parse::<f64>().ok().filter(|v| *v >= 0.0)?;
+    }
+
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+/// Maps `!`, `?`, `!!`, `??`, `!?` and `?!` to their NAGs.
+fn suffix_nag(suffix: &str) -> Option<u8> {
+    match suffix {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+}
+
+impl PgnMove {
+    #[must_use]
+    pub fn new(mv: Move, san: String) -> Self {
+        PgnMove {
+            mv,
+            san,
+            nags: Vec::new(),
+            comments_before: Vec::new(),
+            comments: Vec::new(),
+            clock: None,
+            eval: None,
+            variations: Vec::new(),
+        }
+    }
+
+    /// Adds a comment, moving its `[%clk]` and `[%eval]` commands to `clock` and `eval`.
+    fn add_comment(&mut self, comment: &str) {
+        let mut text = String::new();
+        let mut rest = comment;
+
+        while let Some(start) = rest.find("[%") {
+            let Some(len) = rest[start..].find(']') else {
+                break;
+            };
+            let command = &rest[start + 2..start + len];
+            let (name, value) = command
+                .split_once(char::is_whitespace)
+                .unwrap_or((command, ""));
+
+            let parsed = match name {
+                "clk" => parse_clock(value.trim())
+                    .inspect(|clock| self.clock = Some(*clock))
+                    .is_some(),
+                "eval" => PgnEval::from_str(value.trim())
+                    .ok()
+                    .inspect(|eval| self.eval = Some(*eval))
+                    .is_some(),
+                _ => false,
+            };
+
+            text.push_str(&rest[..start]);
+            if !parsed {
+                text.push_str(&rest[start..=start + len]);
+            }
+            rest = &rest[start + len + 1..];
+        }
+        text.push_str(rest);
+
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            self.comments.push(text);
+        }
+    }
+}
+
+impl PgnGame {
+    #[must_use]
+    pub fn new(start: Position) -> Self {
+        PgnGame {
+            tags: Vec::new(),
+            game: Game::new(start),
+            moves: Vec::new(),
+            result: PgnResult::Unknown,
+        }
+    }
+
+    /// Annotates the moves of `game` with their SAN, the result is set if the game is over.
+    #[must_use]
+    pub fn from_game(move_gen: &MoveGen, game: Game) -> Self {
+        let mut position = game.start().clone();
+        let moves = game
+            .moves()
+            .map(|mv| {
+                let san = move_gen.to_algebraic_notation(&position, mv);
+                position.make_move(mv).expect("BUG: Game move not playable");
+
+                PgnMove::new(mv.clone(), san)
+            })
+            .collect();
+
+        PgnGame {
+            tags: Vec::new(),
+            result: game.result(move_gen).into(),
+            game,
+            moves,
+        }
+    }
+
+    /// Main line without annotations.
+    #[must_use]
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    #[must_use]
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+
+    /// Taken from the `FEN` tag, the standard starting position otherwise.
+    #[must_use]
+    pub fn start(&self) -> &Position {
+        self.game.start()
+    }
+
+    /// Position after the last main line move.
+    #[must_use]
+    pub fn position(&self) -> &Position {
+        self.game.position()
+    }
+
+    /// Main line with annotations.
+    #[must_use]
+    pub fn moves(&self) -> &[PgnMove] {
+        &self.moves
+    }
+
+    /// Main line to edit annotations, moves are added with [`PgnGame::push`].
+    pub fn moves_mut(&mut self) -> &mut [PgnMove] {
+        &mut self.moves
+    }
+
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the tag, keeping its place if it already exists.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Plays a legal move at the end of the main line and returns it, to add annotations.
+    pub fn push(&mut self, move_gen: &MoveGen, mv: Move) -> Result<&mut PgnMove, PgnError> {
+        let before = self.game.position().clone();
+        self.game
+            .push(move_gen, mv.clone())
+            .map_err(|_| PgnError::IllegalMove {
+                san: mv.to_uci(),
+                fen: before.to_fen(),
+            })?;
+
+        let san = move_gen.to_algebraic_notation(&before, &mv);
+        self.moves.push(PgnMove::new(mv, san));
+
+        Ok(self.moves.last_mut().expect("BUG: Move was just pushed"))
+    }
+
+    /// Parses a single game, see [`PgnReader`] for files with several games.
+    pub fn parse(move_gen: &MoveGen, pgn: &str) -> Result<PgnGame, PgnError> {
+        let mut tags = Vec::new();
+        let mut movetext = String::new();
+        for line in pgn.lines() {
+            if movetext.trim().is_empty() && line.trim_start().starts_with('[') {
+                tags.push(parse_tag(line)?);
+            } else {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+
+        let start = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => {
+                Position::from_fen_strict(fen.clone()).map_err(PgnError::InvalidFen)?
+            }
+            None => Position::default(),
+        };
+
+        let mut game = PgnGame::new(start);
+        game.tags = tags;
+        MovetextParser::new(move_gen, &mut game).parse(&movetext)?;
+
+        Ok(game)
+    }
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), PgnError> {
+    let invalid = || PgnError::InvalidTag(line.to_string());
+
+    let inner = line
+        .trim()
+        .strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let (name, value) = inner
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(chars.next().ok_or_else(invalid)?),
+            '"' => return Err(invalid()),
+            c => unescaped.push(c),
+        }
+    }
+
+    Ok((name.to_string(), unescaped))
+}
+
+/// A line of moves being read, the main line or a variation.
+struct Line {
+    moves: Vec<PgnMove>,
+    /// Position the next move is played in.
+    position: Position,
+    /// Position before the last move, where its variations start.
+    before_last: Position,
+    /// Comments waiting for the next move.
+    comments: Vec<String>,
+}
+
+struct MovetextParser<'a> {
+    move_gen: &'a MoveGen,
+    game: &'a mut PgnGame,
+    lines: Vec<Line>,
+}
+
+impl<'a> MovetextParser<'a> {
+    fn new(move_gen: &'a MoveGen, game: &'a mut PgnGame) -> Self {
+        let main = Line {
+            moves: Vec::new(),
+            position: game.start().clone(),
+            before_last: game.start().clone(),
+            comments: Vec::new(),
+        };
+
+        MovetextParser {
+            move_gen,
+            game,
+            lines: vec![main],
+        }
+    }
+
+    fn line(&mut self) -> &mut Line {
+        self.lines
+            .last_mut()
+            .expect("BUG: Main line is never popped")
+    }
+
+    fn comment(&mut self, comment: &str) {
+        let line = self.line();
+        match line.moves.last_mut() {
+            Some(mv) if line.comments.is_empty() => mv.add_comment(comment),
+            _ => line.comments.push(comment.trim().to_string()),
+        }
+    }
+
+    fn nag(&mut self, nag: u8, token: &str) -> Result<(), PgnError> {
+        let mv = self.line().moves.last_mut();
+        let mv = mv.ok_or_else(|| PgnError::UnexpectedToken(token.to_string()))?;
+        mv.nags.push(nag);
+
+        Ok(())
+    }
+
+    fn san(&mut self, token: &str) -> Result<(), PgnError> {
+        let suffix_start = token.trim_end_matches(['!', '?']).len();
+        let (san, suffix) = token.split_at(suffix_start);
+
+        let move_gen = self.move_gen;
+        let line = self.line();
+        let mv = move_gen
+            .parse_san(&line.position, san)
+            .ok_or_else(|| PgnError::IllegalMove {
+                san: san.to_string(),
+                fen: line.position.to_fen(),
+            })?;
+
+        line.before_last = line.position.clone();
+        line.position
+            .make_move(&mv)
+            .map_err(|_| PgnError::IllegalMove {
+                san: san.to_string(),
+                fen: line.before_last.to_fen(),
+            })?;
+
+        let mut pgn_move = PgnMove::new(mv, san.to_string());
+        pgn_move.comments_before = std::mem::take(&mut line.comments);
+        line.moves.push(pgn_move);
+
+        if !suffix.is_empty() {
+            let nag = suffix_nag(suffix).ok_or_else(|| PgnError::UnexpectedToken(token.into()))?;
+            self.nag(nag, token)?;
+        }
+
+        Ok(())
+    }
+
+    fn start_variation(&mut self) -> Result<(), PgnError> {
+        let line = self.line();
+        if line.moves.is_empty() {
+            return Err(PgnError::UnbalancedVariation);
+        }
+
+        let start = line.before_last.clone();
+        self.lines.push(Line {
+            moves: Vec::new(),
+            position: start.clone(),
+            before_last: start,
+            comments: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    fn end_variation(&mut self) -> Result<(), PgnError> {
+        if self.lines.len() == 1 {
+            return Err(PgnError::UnbalancedVariation);
+        }
+
+        let mut variation = self.lines.pop().expect("BUG: Length checked above");
+        if let Some(mv) = variation.moves.last_mut() {
+            mv.comments.append(&mut variation.comments);
+        }
+        if !variation.moves.is_empty() {
+            self.line()
+                .moves
+                .last_mut()
+                .expect("BUG: Variation follows a move")
+                .variations
+                .push(variation.moves);
+        }
+
+        Ok(())
+    }
+
+    fn parse(mut self, movetext: &str) -> Result<(), PgnError> {
+        let mut chars = movetext.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            match c {
+                c if c.is_whitespace() => {}
+                '{' => {
+                    let len = movetext[start..]
+                        .find('}')
+                        .ok_or(PgnError::UnterminatedComment)?;
+                    self.comment(&movetext[start + 1..start + len]);
+                    while chars.next_if(|(idx, _)| *idx <= start + len).is_some() {}
+                }
+                ';' => {
+                    let len = movetext[start..]
+                        .find('\n')
+                        .unwrap_or(movetext.len() - start);
+                    self.comment(&movetext[start + 1..start + len]);
+                    while chars.next_if(|(idx, _)| *idx < start + len).is_some() {}
+                }
+                '(' => self.start_variation()?,
+                ')' => self.end_variation()?,
+                _ => {
+                    let mut end = movetext.len();
+                    while let Some((idx, c)) = chars.peek() {
+                        if c.is_whitespace() || "{};()".contains(*c) {
+                            end = *idx;
+                            break;
+                        }
+                        chars.next();
+                    }
+
+                    if self.token(&movetext[start..end])? {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if self.lines.len() != 1 {
+            return Err(PgnError::UnbalancedVariation);
+        }
+
+        let main = self.lines.pop().expect("BUG: Length checked above");
+        let mut moves = main.moves;
+        if let Some(mv) = moves.last_mut() {
+            mv.comments.extend(main.comments);
+        }
+        for mv in &moves {
+            self.game
+                .game
+                .push(self.move_gen, mv.mv.clone())
+                .expect("BUG: Parsed move not legal");
+        }
+        self.game.moves = moves;
+
+        Ok(())
+    }
+
+    /// Handles a symbol token, returns true for the game termination marker.
+    fn token(&mut self, token: &str) -> Result<bool, PgnError> {
+        if let Ok(result) = PgnResult::from_str(token) {
+            if self.lines.len() != 1 {
+                return Err(PgnError::UnbalancedVariation);
+            }
+            self.game.result = result;
+            return Ok(true);
+        }
+
+        if let Some(nag) = token.strip_prefix('$') {
+            let nag = nag
+                .parse()
+                .map_err(|_| PgnError::UnexpectedToken(token.to_string()))?;
+            self.nag(nag, token)?;
+            return Ok(false);
+        }
+
+        if let Some(nag) = suffix_nag(token) {
+            self.nag(nag, token)?;
+            return Ok(false);
+        }
+
+        // Move numbers, possibly glued to the move as in `12.Nf3` or `12...Nf3`.
+        let san = token.trim_start_matches(|c: char| c.is_ascii_digit());
+        let san = if san.len() != token.len() && san.starts_with('.') {
+            san.trim_start_matches('.')
+        } else {
+            token
+        };
+        if !san.is_empty() {
+            self.san(san)?;
+        }
+
+        Ok(false)
+    }
+}
+
+/// Streams games out of a PGN file. Games are split at the tag pairs following a movetext,
+/// an invalid game is reported as an error and reading continues with the next one.
+pub struct PgnReader<'a, R> {
+    reader: R,
+    move_gen: &'a MoveGen,
+    /// First line of the next game, read while looking for the end of the previous one.
+    pending: Option<String>,
+}
+
+impl<'a, R: BufRead> PgnReader<'a, R> {
+    pub fn new(reader: R, move_gen: &'a MoveGen) -> Self {
+        PgnReader {
+            reader,
+            move_gen,
+            pending: None,
+        }
+    }
+
+    /// Reads the text of the next game, `None` at the end of the input.
+    fn read_game_text(&mut self) -> io::Result<Option<String>> {
+        let mut text = self.pending.take().unwrap_or_default();
+        let mut in_movetext = false;
+        let mut in_comment = false;
+
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                break;
+            }
+
+            // Escape mechanism of the standard, the whole line is ignored.
+            if line.starts_with('%') {
+                continue;
+            }
+
+            let trimmed = line.trim_start();
+            if !in_comment && trimmed.starts_with('[') {
+                if in_movetext {
+                    self.pending = Some(line);
+                    break;
+                }
+            } else if !trimmed.is_empty() {
+                in_movetext = true;
+                in_comment = ends_in_comment(trimmed, in_comment);
+            }
+
+            text.push_str(&line);
+        }
+
+        if text.trim().is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(text))
+        }
+    }
+}
+
+/// Returns whether a brace comment is still open at the end of the line.
+fn ends_in_comment(line: &str, mut in_comment: bool) -> bool {
+    for c in line.chars() {
+        match c {
+            '{' if !in_comment => in_comment = true,
+            '}' if in_comment => in_comment = false,
+            ';' if !in_comment => break,
+            _ => {}
+        }
+    }
+
+    in_comment
+}
+
+impl<R: BufRead> Iterator for PgnReader<'_, R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_game_text() {
+            Ok(Some(text)) => Some(PgnGame::parse(self.move_gen, &text)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err.into())),
+        }
+    }
+}
+
+/// Collects movetext tokens and wraps them into lines.
+#[derive(Default)]
+struct MovetextWriter {
+    lines: Vec<String>,
+    current: String,
+    /// Opening parenthesis glued to the next token.
+    open_variation: bool,
+}
+
+impl MovetextWriter {
+    fn push(&mut self, token: &str) {
+        let token = if std::mem::take(&mut self.open_variation) {
+            format!("({token}")
+        } else {
+            token.to_string()
+        };
+
+        if !self.current.is_empty() && self.current.len() + 1 + token.len() > LINE_LENGTH {
+            self.lines.push(std::mem::take(&mut self.current));
+        }
+        if !self.current.is_empty() {
+            self.current.push(' ');
+        }
+        self.current.push_str(&token);
+    }
+
+    fn comment(&mut self, comment: &str) {
+        self.push(&format!("{{{comment}}}"));
+    }
+
+    fn finish(mut self) -> String {
+        if !self.current.is_empty() {
+            self.lines.push(self.current);
+        }
+        self.lines.join("\n")
+    }
+
+    /// Writes the moves of a line starting at `fullmove` with `turn` to move.
+    fn moves(&mut self, moves: &[PgnMove], mut fullmove: u16, mut turn: Color) {
+        let mut number_needed = true;
+
+        for mv in moves {
+            for comment in &mv.comments_before {
+                self.comment(comment);
+                number_needed = true;
+            }
+
+            match turn {
+                Color::White => self.push(&format!("{fullmove}. {}", mv.san)),
+                Color::Black if number_needed => self.push(&format!("{fullmove}... {}", mv.san)),
+                Color::Black => self.push(&mv.san),
+            }
+            number_needed = false;
+
+            for nag in &mv.nags {
+                self.push(&format!("${nag}"));
+            }
+
+            let mut commands = Vec::new();
+            if let Some(eval) = mv.eval {
+                commands.push(format!("[%eval {eval}]"));
+            }
+            if let Some(clock) = mv.clock {
+                commands.push(format!("[%clk {}]", format_clock(clock)));
+            }
+            if !commands.is_empty() {
+                self.comment(&commands.join(" "));
+                number_needed = true;
+            }
+            for comment in &mv.comments {
+                self.comment(comment);
+                number_needed = true;
+            }
+
+            for variation in &mv.variations {
+                self.open_variation = true;
+                self.moves(variation, fullmove, turn);
+                self.current.push(')');
+                number_needed = true;
+            }
+
+            if turn == Color::Black {
+                fullmove += 1;
+            }
+            turn = turn.enemy();
+        }
+    }
+}
+
+impl Display for PgnGame {
+    /// Writes the tag pairs, adding a `Result` tag if missing, and the movetext wrapped at
+    /// 80 columns.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.tags {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+        if self.tag("Result").is_none() {
+            writeln!(f, "[Result \"{}\"]", self.result)?;
+        }
+        writeln!(f)?;
+
+        let mut writer = MovetextWriter::default();
+        writer.moves(&self.moves, self.start().fullmove_number, self.start().turn);
+        writer.push(&self.result.to_string());
+
+        writeln!(f, "{}", writer.finish())
+    }
+}
+
+/// Writes games separated by empty lines.
+pub struct PgnWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> PgnWriter<W> {
+    pub fn new(writer: W) -> Self {
+        PgnWriter { writer }
+    }
+
+    pub fn write(&mut self, game: &PgnGame) -> io::Result<()> {
+        writeln!(self.writer, "{game}")
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -1,17 +1,57 @@
 #![allow(dead_code)]
-use std::{collections::HashSet, thread};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::HashSet,
+    str::FromStr,
+    thread,
+};
 
+use rand::{rngs::StdRng, SeedableRng};
 use sdk::{
-    fen::Fen,
-    position::{Color, Position},
+    epd::Epd,
+    fen::{Fen, FenError},
+    position::{Color, Piece, Position},
+    square::Square,
+    svg::Arrow,
 };
 use serde::Deserialize;
 
 use crate::{
-    generators::movegen::MoveGen,
-    utils::{chess_notation::ChessNotation, logger::configure_logger},
+    game::{Game, GameResult, Termination},
+    generators::movegen::{GenType, MoveGen},
+    lookup::SliderBackend,
+    perft::{divide, perft, perft_detailed, run_suite, PerftStats, PERFT_SUITE},
+    pgn::{PgnError, PgnEval, PgnGame, PgnReader, PgnResult},
+    r#move::{list::MoveList, MakeMove, Move, MoveKind},
+    utils::{chess_notation::ChessNotation, epd::EpdMoves, logger::configure_logger},
 };
 
+/// Counts the allocations of the current thread, so tests running in parallel don't interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct Test {
@@ -46,36 +86,217 @@ fn load_test(file_name: String) -> Test {
     serde_json::from_str(&test).unwrap()
 }
 
+#[cfg(feature = "magic")]
 #[test]
 fn test_all() {
     configure_logger();
     info!("Starting tests");
 
+    run_with_large_stack(|| run_all_tests(SliderBackend::Magic));
+}
+
+#[test]
+fn test_all_kogge_stone() {
+    run_with_large_stack(|| run_all_tests(SliderBackend::KoggeStone));
+}
+
+#[test]
+fn test_incremental_state() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_incremental_state_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_make_undo() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_make_undo_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_en_passant() {
+    run_with_large_stack(run_en_passant_test);
+}
+
+#[test]
+fn test_move_list() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_move_list_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_pins_and_evasions() {
+    run_with_large_stack(run_pins_and_evasions_test);
+}
+
+#[test]
+fn test_gen_types() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_gen_types_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_perft() {
+    run_with_large_stack(run_perft_test);
+}
+
+#[test]
+fn test_move_arrows() {
+    run_with_large_stack(run_move_arrows_test);
+}
+
+#[test]
+fn test_no_allocations() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_no_allocations_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_symmetry() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_symmetry_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_uci() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_uci_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_san() {
+    run_with_large_stack(|| {
+        for file_name in test_files() {
+            run_san_test(file_name);
+        }
+    });
+}
+
+#[test]
+fn test_epd() {
+    run_with_large_stack(run_epd_test);
+}
+
+#[test]
+fn test_pgn() {
+    run_with_large_stack(run_pgn_test);
+}
+
+#[test]
+fn test_game() {
+    run_with_large_stack(run_game_test);
+}
+
+#[test]
+fn test_chess960() {
+    run_with_large_stack(run_chess960_test);
+}
+
+#[test]
+fn test_random_positions() {
+    run_with_large_stack(|| run_random_positions_test(0));
+}
+
+fn run_with_large_stack(f: impl FnOnce() + Send + 'static) {
     let child = thread::Builder::new()
         .stack_size(32 * 1024 * 1024)
-        .spawn(run_all_tests)
+        .spawn(f)
         .unwrap();
 
     // Wait for thread to join
     child.join().unwrap();
 }
 
-fn run_all_tests() {
+fn test_files() -> impl Iterator<Item = String> {
     let test_dir = std::fs::read_dir("src/test_cases").unwrap();
 
-    for file in test_dir {
-        let file_name = file.unwrap().file_name().into_string().unwrap();
-        info!("Running tests for {}", file_name);
-        run_test(file_name);
+    test_dir.map(|file| file.unwrap().file_name().into_string().unwrap())
+}
+
+fn run_all_tests(backend: SliderBackend) {
+    for file_name in test_files() {
+        info!("Running tests for {} ({:?})", file_name, backend);
+        run_test(file_name, backend);
     }
 }
 
-fn run_test(json_name: String) {
+fn run_incremental_state_test(json_name: String) {
     let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+
+    for test_case in &test_cases.test_cases {
+        let pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
+
+        for mv in move_gen.generate_legal_moves(&pos) {
+            let mut after = pos.clone();
+            after.make_move(&mv).unwrap();
+
+            let mut refreshed = after.clone();
+            refreshed.refresh_occupancy();
+            assert_eq!(
+                after.board, refreshed.board,
+                "{mv} in {}",
+                test_case.start.fen
+            );
+            assert_eq!(
+                after.colors, refreshed.colors,
+                "{mv} in {}",
+                test_case.start.fen
+            );
+            assert_eq!(
+                after.occupied, refreshed.occupied,
+                "{mv} in {}",
+                test_case.start.fen
+            );
+
+            assert_eq!(
+                after.hash,
+                after.compute_hash(),
+                "{mv} in {}",
+                test_case.start.fen
+            );
+            assert_eq!(
+                after.pawn_hash,
+                after.compute_pawn_hash(),
+                "{mv} in {}",
+                test_case.start.fen
+            );
+            assert_eq!(
+                after.material_hash,
+                after.compute_material_hash(),
+                "{mv} in {}",
+                test_case.start.fen
+            );
+        }
+    }
+}
+
+fn run_test(json_name: String, backend: SliderBackend) {
+    let move_gen = MoveGen::with_backend(backend);
     let test_cases = load_test(json_name.clone());
 
     for (idx, test_case) in test_cases.test_cases.iter().enumerate() {
-        let pos = Position::from_fen(test_case.start.fen.clone()).unwrap();
+        let pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
 
         let expected_moves: HashSet<String> = test_case
             .expected
@@ -110,6 +331,820 @@ fn run_test(json_name: String) {
             test_case.start.description,
             test_case.start.fen
         );
+
+        for expected in &test_case.expected {
+            let mv = move_gen
+                .generate_legal_moves(&pos)
+                .find(|mv| move_gen.to_algebraic_notation(&pos, mv) == expected.r#move)
+                .unwrap();
+
+            let mut after = pos.clone();
+            after.make_move(&mv).unwrap();
+            assert_eq!(
+                after.to_fen(),
+                expected.fen,
+                "{} in {}",
+                expected.r#move,
+                test_case.start.fen
+            );
+        }
         info!("[{} ({})] passed.", json_name, idx + 1);
     }
 }
+
+fn run_symmetry_test(json_name: String) {
+    let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+
+    let moves = |pos: &Position, map_square: fn(Square) -> Square| {
+        move_gen
+            .generate_legal_moves(pos)
+            .map(|mv| (map_square(mv.from()), map_square(mv.to()), mv.promotion()))
+            .collect::<HashSet<_>>()
+    };
+
+    for test_case in &test_cases.test_cases {
+        let pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
+
+        assert_eq!(
+            moves(&pos, Square::flip_vertical),
+            moves(&pos.color_flipped(), |square| square),
+            "{}",
+            test_case.start.fen
+        );
+
+        if pos.castling.is_empty() {
+            assert_eq!(
+                moves(&pos, Square::mirror_horizontal),
+                moves(&pos.mirrored(), |square| square),
+                "{}",
+                test_case.start.fen
+            );
+        }
+    }
+}
+
+fn run_uci_test(json_name: String) {
+    let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+
+    for test_case in &test_cases.test_cases {
+        let pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
+
+        for mv in move_gen.generate_legal_moves(&pos) {
+            let uci = mv.to_uci();
+            assert_eq!(move_gen.parse_uci(&pos, &uci), Some(mv.clone()), "{uci}");
+            let uci_chess960 = mv.to_uci_chess960();
+            assert_eq!(
+                move_gen.parse_uci(&pos, &uci_chess960),
+                Some(mv.clone()),
+                "{uci_chess960}"
+            );
+
+            #[cfg(feature = "serde")]
+            {
+                let json = serde_json::to_string(&mv).unwrap();
+                assert_eq!(json, format!("\"{uci}\""));
+
+                let parsed: crate::r#move::Move = serde_json::from_str(&json).unwrap();
+                assert_eq!(parsed.to_uci(), uci);
+                assert_eq!(
+                    move_gen.parse_uci(&pos, &parsed.to_uci()),
+                    Some(mv),
+                    "{uci}"
+                );
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&pos).unwrap();
+            assert_eq!(serde_json::from_str::<Position>(&json).unwrap(), pos);
+        }
+    }
+
+    // Castling with the king staying on g1 is written as king takes rook.
+    #[cfg(feature = "serde")]
+    {
+        let pos = Position::from_fen_strict("4k3/8/8/8/8/8/8/6KR w H - 0 1".to_string()).unwrap();
+        let castle = move_gen
+            .generate_legal_moves(&pos)
+            .find(|mv| matches!(mv.kind(), MoveKind::Castling))
+            .unwrap();
+        let json = serde_json::to_string(&castle).unwrap();
+        assert_eq!(json, "\"g1h1\"");
+
+        let parsed: Move = serde_json::from_str(&json).unwrap();
+        assert_eq!(move_gen.parse_uci(&pos, &parsed.to_uci()), Some(castle));
+    }
+
+    #[cfg(feature = "serde")]
+    for json in [
+        "\"e2e2\"",
+        "\"e2e4q\"",
+        "\"e7e8k\"",
+        "\"e7e8qq\"",
+        "\"e9e8\"",
+        "4120",
+    ] {
+        assert!(serde_json::from_str::<Move>(json).is_err(), "{json}");
+    }
+}
+
+fn run_san_test(json_name: String) {
+    let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+
+    for test_case in &test_cases.test_cases {
+        let pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
+
+        for mv in move_gen.generate_legal_moves(&pos) {
+            let san = move_gen.to_algebraic_notation(&pos, &mv);
+            assert_eq!(move_gen.parse_san(&pos, &san), Some(mv.clone()), "{san}");
+            assert_eq!(move_gen.parse_move(&pos, &mv.to_uci()), Some(mv));
+        }
+    }
+}
+
+fn run_epd_test() {
+    let move_gen = MoveGen::new();
+
+    let epd = Epd::from_str(
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+    )
+    .unwrap();
+    let best = move_gen.best_moves(&epd).unwrap();
+    assert_eq!(best.iter().map(Move::to_uci).collect::<Vec<_>>(), ["g3g6"]);
+    assert!(move_gen.avoid_moves(&epd).unwrap().is_empty());
+
+    let epd = Epd::from_str(
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - bm O-O-O e1g1; am Rxa8+; pv Rxa8+ Kd7 Raxh8;",
+    )
+    .unwrap();
+    let uci = |moves: Vec<Move>| moves.iter().map(Move::to_uci).collect::<Vec<_>>();
+    assert_eq!(uci(move_gen.best_moves(&epd).unwrap()), ["e1c1", "e1g1"]);
+    assert_eq!(uci(move_gen.avoid_moves(&epd).unwrap()), ["a1a8"]);
+    assert_eq!(
+        uci(move_gen.predicted_variation(&epd).unwrap()),
+        ["a1a8", "e8d7", "a8h8"]
+    );
+
+    let epd = Epd::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - bm Nf3;").unwrap();
+    assert!(move_gen.best_moves(&epd).is_err());
+}
+
+const PGN: &str = r#"[Event "Casual game"]
+[White "A \"quoted\" name"]
+[Black "B"]
+[Result "1-0"]
+
+{Opening comment} 1. e4 {[%eval 0.25] [%clk 0:05:00]} e5 2. Nf3 Nc6 (2... d6 $6 3. d4
+(3. Bc4) Nf6; rest of line comment
+) 3.Bb5! a6?! 4. Ba4 Nf6 5. O-O Be7 {Closed} 1-0
+
+% escaped line
+[Event "Second"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 40"]
+
+40. e4 Kd7 41. e5 1/2-1/2
+
+[Event "Broken"]
+
+1. e4 e5 2. Ke3 *
+
+[Event "Last"]
+
+1. d4 {[%eval #-3] [%csl Ga1] note} *
+"#;
+
+fn run_pgn_test() {
+    let move_gen = MoveGen::new();
+    let games = PgnReader::new(PGN.as_bytes(), &move_gen).collect::<Vec<_>>();
+    assert_eq!(games.len(), 4);
+
+    let game = games[0].as_ref().unwrap();
+    assert_eq!(game.tag("White"), Some("A \"quoted\" name"));
+    assert_eq!(game.result, PgnResult::WhiteWins);
+    assert_eq!(game.moves().len(), 10);
+    assert_eq!(game.moves()[0].comments_before, ["Opening comment"]);
+    assert_eq!(game.moves()[0].eval, Some(PgnEval::Centipawns(25)));
+    assert_eq!(
+        game.moves()[0].clock,
+        Some(std::time::Duration::from_secs(300))
+    );
+    assert!(game.moves()[0].comments.is_empty());
+    assert_eq!(game.moves()[4].nags, [1]);
+    assert_eq!(game.moves()[5].nags, [6]);
+    assert_eq!(game.moves()[9].comments, ["Closed"]);
+
+    let variation = &game.moves()[3].variations[0];
+    assert_eq!(
+        variation
+            .iter()
+            .map(|mv| mv.san.as_str())
+            .collect::<Vec<_>>(),
+        ["d6", "d4", "Nf6"]
+    );
+    assert_eq!(variation[0].nags, [6]);
+    assert_eq!(variation[1].variations[0][0].san, "Bc4");
+    assert_eq!(variation[2].comments, ["rest of line comment"]);
+
+    let game = games[1].as_ref().unwrap();
+    assert_eq!(game.start().fullmove_number, 40);
+    assert_eq!(game.result, PgnResult::Draw);
+    assert_eq!(game.position().to_fen(), "8/3k4/8/4P3/8/8/8/4K3 b - - 0 41");
+
+    assert!(matches!(
+        &games[2],
+        Err(PgnError::IllegalMove { san, .. }) if san == "Ke3"
+    ));
+
+    let game = games[3].as_ref().unwrap();
+    assert_eq!(game.moves()[0].eval, Some(PgnEval::Mate(-3)));
+    assert_eq!(game.moves()[0].comments, ["[%csl Ga1] note"]);
+
+    // Written games read back the same, with a `Result` tag added if it was missing.
+    for game in games.iter().flatten() {
+        let written = game.to_string();
+        let read = PgnGame::parse(&move_gen, &written).unwrap();
+        assert_eq!(read.moves(), game.moves(), "{written}");
+        assert_eq!((read.start(), read.result), (game.start(), game.result));
+        assert_eq!(read.tag("Result"), Some(game.result.to_string().as_str()));
+    }
+    let written = games[0].as_ref().unwrap().to_string();
+    assert!(written.lines().all(|line| line.len() <= 80));
+    assert!(written.split_whitespace().collect::<Vec<_>>().join(" ").contains(
+        "1. e4 {[%eval 0.25] [%clk 0:05:00]} 1... e5 2. Nf3 Nc6 (2... d6 $6 3. d4 (3. Bc4) 3... Nf6"
+    ));
+
+    let mut game = PgnGame::new(Position::default());
+    let mv = move_gen.parse_uci(game.position(), "g1f3").unwrap();
+    game.push(&move_gen, mv).unwrap().eval = Some(PgnEval::Centipawns(-5));
+    assert!(game.to_string().ends_with("1. Nf3 {[%eval -0.05]} *\n"));
+    assert!(matches!(
+        game.push(&move_gen, Move::from_uci("e2e4").unwrap()),
+        Err(PgnError::IllegalMove { san, .. }) if san == "e2e4"
+    ));
+
+    // Mating moves are written with `#`.
+    let mut game = PgnGame::new(Position::default());
+    for uci in ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"] {
+        let mv = move_gen.parse_uci(game.position(), uci).unwrap();
+        game.push(&move_gen, mv).unwrap();
+    }
+    assert!(game
+        .to_string()
+        .ends_with("1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# *\n"));
+
+    // Moves can't be played without kings, so such setups are rejected up front.
+    assert!(matches!(
+        PgnGame::parse(&move_gen, "[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n1. e4 *"),
+        Err(PgnError::InvalidFen(FenError::InvalidPosition(_)))
+    ));
+}
+
+fn run_random_positions_test(seed: u64) {
+    let move_gen = MoveGen::new();
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..1000 {
+        let pos = Position::random(&mut rng);
+
+        for mv in move_gen.generate_legal_moves(&pos) {
+            let mut next = pos.clone();
+            next.make_move(&mv).unwrap();
+
+            let king = next.pieces[pos.turn as usize][Piece::King as usize].lsb();
+            assert!(
+                next.attackers(king, next.turn).is_empty(),
+                "{} leaves the king in check: {}",
+                mv.to_uci(),
+                pos.to_fen()
+            );
+        }
+    }
+}
+
+fn run_chess960_test() {
+    let move_gen = MoveGen::new();
+
+    let after = |pos: &Position, mv: &Move| {
+        let mut pos = pos.clone();
+        pos.make_move(mv).unwrap();
+        pos.to_fen()
+    };
+
+    // Some starting positions can castle right away, e.g. with the king on f1 and rook on g1.
+    for index in 0..sdk::chess960::CHESS960_POSITIONS {
+        let pos = Position::chess960(index);
+        for mv in move_gen.generate_legal_moves(&pos) {
+            let mut after = pos.clone();
+            after.make_move(&mv).unwrap();
+            assert_eq!(after.validate(), Ok(()), "{index} {mv}");
+        }
+    }
+    let pos = Position::chess960(3);
+    let castle = move_gen.parse_san(&pos, "O-O").unwrap();
+    assert_eq!(castle.to_uci_chess960(), "f1g1");
+    assert_eq!(
+        after(&pos, &castle),
+        "bqnnrkrb/pppppppp/8/8/8/8/PPPPPPPP/BQNNRRKB b kq - 1 1"
+    );
+
+    // Standard castling is encoded as king takes rook too.
+    let pos =
+        Position::from_fen_strict("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string()).unwrap();
+    let castle = move_gen.parse_uci(&pos, "e1g1").unwrap();
+    assert_eq!(move_gen.parse_uci(&pos, "e1h1"), Some(castle.clone()));
+    assert_eq!(castle.to_uci(), "e1g1");
+    assert_eq!(castle.to_uci_chess960(), "e1h1");
+    assert_eq!(after(&pos, &castle), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+
+    // Both castlings land the king next to its start, b1c1 is a plain king move.
+    let pos = Position::from_fen_strict("4k3/8/8/8/8/8/8/RK4R1 w GA - 0 1".to_string()).unwrap();
+    let castlings = move_gen
+        .generate_legal_moves(&pos)
+        .filter(|mv| matches!(mv.kind(), MoveKind::Castling))
+        .map(|mv| move_gen.to_algebraic_notation(&pos, &mv))
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        castlings,
+        HashSet::from(["O-O".to_string(), "O-O-O".to_string()])
+    );
+
+    let queenside = move_gen.parse_uci(&pos, "b1a1").unwrap();
+    assert_eq!(move_gen.parse_san(&pos, "O-O-O"), Some(queenside.clone()));
+    assert!(!matches!(
+        move_gen.parse_uci(&pos, "b1c1").unwrap().kind(),
+        MoveKind::Castling
+    ));
+    assert_eq!(after(&pos, &queenside), "4k3/8/8/8/8/8/8/2KR2R1 b - - 1 1");
+
+    let kingside = move_gen.parse_uci(&pos, "b1g1").unwrap();
+    assert_eq!(move_gen.parse_san(&pos, "O-O"), Some(kingside.clone()));
+    assert_eq!(after(&pos, &kingside), "4k3/8/8/8/8/8/8/R4RK1 b - - 1 1");
+
+    let mut undone = pos.clone();
+    let state = undone.make_move(&kingside).unwrap();
+    undone.undo_move(&kingside, &state).unwrap();
+    assert_eq!(undone.board, pos.board);
+    assert_eq!(undone, pos);
+
+    // The castling rook shields the king target from the queen.
+    let pos = Position::from_fen_strict("4k3/8/8/8/8/8/8/qRK5 w B - 0 1".to_string()).unwrap();
+    assert!(move_gen
+        .generate_legal_moves(&pos)
+        .all(|mv| !matches!(mv.kind(), MoveKind::Castling)));
+}
+
+fn run_game_test() {
+    let move_gen = MoveGen::new();
+
+    let play = |game: &mut Game, moves: &str| {
+        for san in moves.split_whitespace() {
+            let mv = move_gen.parse_san(game.position(), san).unwrap();
+            game.push(&move_gen, mv).unwrap();
+        }
+    };
+
+    let mut game = Game::default();
+    play(&mut game, "Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1");
+    assert_eq!(game.repetitions(), 2);
+    assert_eq!(game.claimable_draw(), None);
+    play(&mut game, "Ng8");
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(
+        game.claimable_draw(),
+        Some(Termination::ThreefoldRepetition)
+    );
+    assert_eq!(game.result(&move_gen), GameResult::Ongoing);
+    play(&mut game, "Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1 Ng8");
+    assert_eq!(
+        game.result(&move_gen),
+        GameResult::Draw(Termination::FivefoldRepetition)
+    );
+
+    // Undone moves can be redone until another move is pushed.
+    let mut game = Game::default();
+    play(&mut game, "e4 e5");
+    let after_e5 = game.position().clone();
+    play(&mut game, "Nf3");
+    assert_eq!(game.plies()[2].state.hash, after_e5.hash);
+    assert_eq!(game.undo().map(Move::to_uci), Some("g1f3".to_string()));
+    assert_eq!(game.position(), &after_e5);
+    game.undo();
+    assert_eq!(
+        game.redo(&move_gen).map(Move::to_uci),
+        Some("e7e5".to_string())
+    );
+    assert_eq!(game.position(), &after_e5);
+    play(&mut game, "Nc3");
+    assert_eq!(game.redo(&move_gen), None);
+    assert_eq!(game.pop().map(|mv| mv.to_uci()), Some("b1c3".to_string()));
+    assert_eq!(game.moves().count(), 2);
+    assert!(game
+        .push(&move_gen, Move::from_uci("e4e5").unwrap())
+        .is_err());
+
+    let mut game = Game::default();
+    play(&mut game, "f3 e5 g4 Qh4#");
+    let result = game.result(&move_gen);
+    assert_eq!(
+        result,
+        GameResult::Win {
+            winner: Color::Black,
+            termination: Termination::Checkmate
+        }
+    );
+    assert_eq!(PgnResult::from(result), PgnResult::BlackWins);
+
+    // PGN games are built on a game and convert back to the same one.
+    let pgn = PgnGame::from_game(&move_gen, game.clone());
+    assert_eq!(pgn.result, PgnResult::BlackWins);
+    assert_eq!(
+        pgn.moves()
+            .iter()
+            .map(|mv| mv.san.as_str())
+            .collect::<Vec<_>>(),
+        ["f3", "e5", "g4", "Qh4#"]
+    );
+    let read = PgnGame::parse(&move_gen, &pgn.to_string()).unwrap();
+    assert_eq!(read.into_game(), game);
+
+    for (fen, moves, result, claimable) in [
+        (
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+            "",
+            GameResult::Draw(Termination::Stalemate),
+            None,
+        ),
+        (
+            "8/8/8/8/8/8/2n5/k1K5 b - - 0 1",
+            "Nb4",
+            GameResult::Draw(Termination::InsufficientMaterial),
+            None,
+        ),
+        (
+            "k7/8/8/8/8/8/8/1R2K3 w - - 99 80",
+            "Ke2",
+            GameResult::Ongoing,
+            Some(Termination::FiftyMoveRule),
+        ),
+        (
+            "k7/8/8/8/8/8/8/1R2K3 w - - 149 80",
+            "Ke2",
+            GameResult::Draw(Termination::SeventyFiveMoveRule),
+            Some(Termination::FiftyMoveRule),
+        ),
+    ] {
+        let mut game = Game::new(Position::from_fen_strict(fen.to_string()).unwrap());
+        play(&mut game, moves);
+        assert_eq!(game.result(&move_gen), result, "{fen}");
+        assert_eq!(game.claimable_draw(), claimable, "{fen}");
+    }
+}
+
+fn run_make_undo_test(json_name: String) {
+    let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+
+    for test_case in &test_cases.test_cases {
+        let mut pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
+        let start = pos.clone();
+
+        for mv in move_gen.generate_legal_moves(&start) {
+            let state = pos.make_move(&mv).unwrap();
+            let after = pos.clone();
+
+            // Undo the replies too, they start from state written by `make_move`.
+            for reply in move_gen.generate_legal_moves(&after) {
+                let reply_state = pos.make_move(&reply).unwrap();
+                pos.undo_move(&reply, &reply_state).unwrap();
+                assert_eq!(pos, after, "{mv} {reply} in {}", test_case.start.fen);
+            }
+
+            pos.undo_move(&mv, &state).unwrap();
+            assert_eq!(pos, start, "{mv} in {}", test_case.start.fen);
+        }
+    }
+}
+
+fn run_en_passant_test() {
+    let move_gen = MoveGen::new();
+    let pos = Position::from_fen_strict("4k3/8/8/8/3p4/8/P3P3/4K3 w - - 0 1".to_string()).unwrap();
+
+    let play = |pos: &Position, uci: &str| {
+        let mv = move_gen.parse_uci(pos, uci).unwrap();
+        assert!(matches!(mv.kind(), MoveKind::DoublePawnPush), "{uci}");
+
+        let mut after = pos.clone();
+        after.make_move(&mv).unwrap();
+        after
+    };
+
+    // The en passant square is only set when an enemy pawn can take.
+    let after = play(&pos, "e2e4");
+    assert_eq!(after.to_fen(), "4k3/8/8/8/3pP3/8/P7/4K3 b - e3 0 1");
+    assert_eq!(after.hash, after.compute_hash());
+    assert!(move_gen
+        .generate_legal_moves(&after)
+        .any(|mv| matches!(mv.kind(), MoveKind::EnPassant)));
+
+    let after = play(&pos, "a2a4");
+    assert_eq!(after.to_fen(), "4k3/8/8/8/P2p4/8/4P3/4K3 b - - 0 1");
+
+    // King moves give up castling rights.
+    let pos =
+        Position::from_fen_strict("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".to_string()).unwrap();
+    let mv = move_gen.parse_uci(&pos, "e1d1").unwrap();
+    let mut after = pos.clone();
+    after.make_move(&mv).unwrap();
+    assert_eq!(after.to_fen(), "r3k2r/8/8/8/8/8/8/R2K3R b kq - 1 1");
+}
+
+fn run_move_list_test(json_name: String) {
+    let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+
+    for test_case in &test_cases.test_cases {
+        let pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
+
+        let mut moves = MoveList::new();
+        move_gen.generate_legal_moves_into(&pos, &mut moves);
+        assert_eq!(
+            moves.len(),
+            test_case.expected.len(),
+            "{}",
+            test_case.start.fen
+        );
+        assert!(moves.scores().iter().all(|&score| score == 0));
+
+        let mut visited = Vec::new();
+        move_gen.visit_legal_moves(&pos, |mv| visited.push(mv));
+        assert_eq!(&moves[..], &visited[..], "{}", test_case.start.fen);
+        assert!(move_gen.generate_legal_moves(&pos).eq(visited));
+    }
+
+    let mut moves = MoveList::new();
+    moves.push_scored(Move::from_uci("e2e4").unwrap(), 10);
+    moves.push(Move::from_uci("d2d4").unwrap());
+    moves.swap(0, 1);
+    assert_eq!(moves[0], Move::from_uci("d2d4").unwrap());
+    assert_eq!(moves.scores(), [0, 10]);
+}
+
+fn run_pins_and_evasions_test() {
+    let move_gen = MoveGen::new();
+    let uci_moves = |fen: &str| {
+        let pos = Position::from_fen_strict(fen.to_string()).unwrap();
+        move_gen
+            .generate_legal_moves(&pos)
+            .map(|mv| mv.to_uci())
+            .collect::<HashSet<_>>()
+    };
+
+    // A pinned rook may still move along the pin.
+    let moves = uci_moves("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1");
+    for uci in ["e2e3", "e2e7", "e2e8"] {
+        assert!(moves.contains(uci), "{uci}");
+    }
+    assert!(!moves.contains("e2d2"));
+
+    // A pinned bishop may take its pinner.
+    let moves = uci_moves("4k3/8/8/8/7b/8/5B2/4K3 w - - 0 1");
+    assert!(moves.contains("f2g3") && moves.contains("f2h4"));
+    assert!(!moves.contains("f2e3"));
+
+    // Taking en passant removes the checking pawn.
+    let moves = uci_moves("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1");
+    assert!(moves.contains("e4d3"));
+
+    // Both pawns leave the rank, exposing the king to the rook.
+    let moves = uci_moves("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1");
+    assert!(!moves.contains("b5c6"));
+}
+
+fn run_gen_types_test(json_name: String) {
+    let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+
+    let check_gen_types = |pos: &Position| {
+        let moves = |gen_type| {
+            move_gen
+                .generate_moves(pos, gen_type)
+                .map(|mv| mv.to_uci())
+                .collect::<HashSet<_>>()
+        };
+        let legal = moves(GenType::Legal);
+        let captures = moves(GenType::Captures);
+        let quiets = moves(GenType::Quiets);
+        let fen = pos.to_fen();
+
+        assert!(captures.is_disjoint(&quiets), "{fen}");
+        for mv in move_gen.generate_legal_moves(pos) {
+            if mv.is_capture() {
+                assert!(captures.contains(&mv.to_uci()), "{fen} {}", mv.to_uci());
+            }
+        }
+        assert_eq!(
+            captures.union(&quiets).cloned().collect::<HashSet<_>>(),
+            legal,
+            "{fen}"
+        );
+
+        let in_check = move_gen.is_check(pos);
+        assert_eq!(
+            moves(GenType::Evasions),
+            if in_check { legal } else { HashSet::new() },
+            "{fen}"
+        );
+
+        let quiet_checks = move_gen
+            .generate_moves(pos, GenType::Quiets)
+            .filter(|mv| {
+                let mut after = pos.clone();
+                after.make_move(mv).unwrap();
+                move_gen.is_check(&after)
+            })
+            .map(|mv| mv.to_uci())
+            .collect::<HashSet<_>>();
+        assert_eq!(moves(GenType::QuietChecks), quiet_checks, "{fen}");
+    };
+
+    for test_case in &test_cases.test_cases {
+        let pos = Position::from_fen_strict(test_case.start.fen.clone()).unwrap();
+        check_gen_types(&pos);
+
+        for mv in move_gen.generate_legal_moves(&pos) {
+            let mut after = pos.clone();
+            after.make_move(&mv).unwrap();
+            check_gen_types(&after);
+        }
+    }
+
+    // Discovered checks by a knight and by the king, and a check by castling.
+    for fen in [
+        "1k6/8/8/8/8/8/1N6/1R2K3 w - - 0 1",
+        "k7/8/8/8/8/8/K7/R7 w - - 0 1",
+    ] {
+        let pos = Position::from_fen_strict(fen.to_string()).unwrap();
+        check_gen_types(&pos);
+        assert!(move_gen.generate_moves(&pos, GenType::QuietChecks).count() > 0);
+    }
+    // Capture underpromotions are captures, underpromotions by a push are quiet.
+    let pos = Position::from_fen_strict("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1".to_string()).unwrap();
+    check_gen_types(&pos);
+    let captures = move_gen
+        .generate_moves(&pos, GenType::Captures)
+        .map(|mv| mv.to_uci())
+        .collect::<HashSet<_>>();
+    let quiets = move_gen
+        .generate_moves(&pos, GenType::Quiets)
+        .map(|mv| mv.to_uci())
+        .collect::<HashSet<_>>();
+    for uci in ["a7b8q", "a7b8r", "a7b8b", "a7b8n", "a7a8q"] {
+        assert!(captures.contains(uci), "{uci}");
+    }
+    for uci in ["a7a8r", "a7a8b", "a7a8n"] {
+        assert!(quiets.contains(uci), "{uci}");
+    }
+
+    let pos = Position::from_fen_strict("5k2/8/8/8/8/8/8/4K2R w K - 0 1".to_string()).unwrap();
+    check_gen_types(&pos);
+    assert!(move_gen
+        .generate_moves(&pos, GenType::QuietChecks)
+        .any(|mv| matches!(mv.kind(), MoveKind::Castling)));
+}
+
+fn run_perft_test() {
+    let move_gen = MoveGen::new();
+
+    for result in run_suite(&move_gen, PERFT_SUITE, 100_000) {
+        assert!(
+            result.passed(),
+            "{} at depth {}: expected {}, found {}",
+            result.case.name,
+            result.depth,
+            result.expected,
+            result.actual
+        );
+    }
+
+    let pos = Position::default();
+    let divided = divide(&move_gen, &pos, 3);
+    assert_eq!(divided.len(), 20);
+    assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), 8_902);
+    let (_, e2e4) = divided
+        .iter()
+        .find(|(mv, _)| mv.to_uci() == "e2e4")
+        .unwrap();
+    assert_eq!(*e2e4, 600);
+    assert_eq!(perft(&move_gen, &pos, 0), 1);
+
+    assert_eq!(
+        perft_detailed(&move_gen, &pos, 4),
+        PerftStats {
+            nodes: 197_281,
+            captures: 1_576,
+            en_passant: 0,
+            castles: 0,
+            promotions: 0,
+            checks: 469,
+            discovered_checks: 0,
+            double_checks: 0,
+            checkmates: 8,
+        }
+    );
+
+    let kiwipete = Position::from_fen(PERFT_SUITE[1].fen.to_string()).unwrap();
+    assert_eq!(
+        perft_detailed(&move_gen, &kiwipete, 3),
+        PerftStats {
+            nodes: 97_862,
+            captures: 17_102,
+            en_passant: 45,
+            castles: 3_162,
+            promotions: 0,
+            checks: 993,
+            discovered_checks: 0,
+            double_checks: 0,
+            checkmates: 1,
+        }
+    );
+
+    let position_3 = Position::from_fen(PERFT_SUITE[2].fen.to_string()).unwrap();
+    assert_eq!(
+        perft_detailed(&move_gen, &position_3, 4),
+        PerftStats {
+            nodes: 43_238,
+            captures: 3_348,
+            en_passant: 123,
+            castles: 0,
+            promotions: 0,
+            checks: 1_680,
+            discovered_checks: 106,
+            double_checks: 0,
+            checkmates: 17,
+        }
+    );
+
+    // Counts double checks apart from discovered ones.
+    assert_eq!(
+        perft_detailed(&move_gen, &position_3, 5),
+        PerftStats {
+            nodes: 674_624,
+            captures: 52_051,
+            en_passant: 1_165,
+            castles: 0,
+            promotions: 0,
+            checks: 52_950,
+            discovered_checks: 1_292,
+            double_checks: 3,
+            checkmates: 0,
+        }
+    );
+}
+
+fn run_move_arrows_test() {
+    let move_gen = MoveGen::new();
+    let arrow = |fen: &str, uci: &str| {
+        let pos = Position::from_fen_strict(fen.to_string()).unwrap();
+        Arrow::from(&move_gen.parse_uci(&pos, uci).unwrap())
+    };
+
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(arrow(start, "g1f3"), Arrow::new(Square::G1, Square::F3));
+
+    // Castling points to where the king lands, not to the rook it takes.
+    let castling = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(arrow(castling, "e1h1"), Arrow::new(Square::E1, Square::G1));
+    assert_eq!(arrow(castling, "e1a1"), Arrow::new(Square::E1, Square::C1));
+    assert_eq!(
+        arrow("4k3/8/8/8/8/8/8/RK4R1 w GA - 0 1", "b1g1"),
+        Arrow::new(Square::B1, Square::G1)
+    );
+}
+
+fn run_no_allocations_test(json_name: String) {
+    let move_gen = MoveGen::new();
+    let test_cases = load_test(json_name);
+    let positions = test_cases
+        .test_cases
+        .iter()
+        .map(|test_case| Position::from_fen_strict(test_case.start.fen.clone()).unwrap())
+        .collect::<Vec<_>>();
+    let mut moves = MoveList::new();
+
+    for pos in &positions {
+        let before = allocations();
+
+        let mut visited = 0;
+        move_gen.visit_legal_moves(pos, |_| visited += 1);
+        moves.clear();
+        move_gen.generate_legal_moves_into(pos, &mut moves);
+
+        assert_eq!(allocations(), before, "{}", pos.to_fen());
+        assert_eq!(moves.len(), visited);
+    }
+}
@@ -1,7 +1,13 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::str::FromStr;
+
 use itertools::Itertools;
 use sdk::{
     position::{Piece, Position},
-    square::Square,
+    square::{File, Rank, Square},
 };
 
 use crate::{
@@ -12,6 +18,12 @@ use crate::{
 pub trait ChessNotation {
     fn get_from_notation(&self, pos: &Position, mv: &Move) -> (String, String);
     fn to_algebraic_notation(&self, pos: &Position, mv: &Move) -> String;
+    fn parse_uci(&self, pos: &Position, uci: &str) -> Option<Move>;
+    /// Parses a move in standard algebraic notation, e.g. `Nbd2`, `exd6`, `e8=Q+` or `O-O`.
+    /// Returns `None` unless exactly one legal move matches.
+    fn parse_san(&self, pos: &Position, san: &str) -> Option<Move>;
+    /// Parses a move in SAN or UCI form, as found in EPD and PGN files.
+    fn parse_move(&self, pos: &Position, mv: &str) -> Option<Move>;
 }
 
 impl ChessNotation for MoveGen {
@@ -57,21 +69,96 @@ impl ChessNotation for MoveGen {
         }
     }
 
+    /// Castling is accepted both as the king move, e.g. `e1g1`, and as king takes rook, e.g.
+    /// `e1h1`. The latter is how Chess960 GUIs send it, where the king move form may also be a
+    /// plain king move, which then takes precedence.
+    fn parse_uci(&self, pos: &Position, uci: &str) -> Option<Move> {
+        let parsed = Move::from_uci(uci).ok()?;
+
+        let candidates = self
+            .generate_legal_moves(pos)
+            .filter(|mv| mv.from() == parsed.from() && mv.promotion() == parsed.promotion())
+            .collect_vec();
+
+        candidates
+            .iter()
+            .find(|mv| mv.to() == parsed.to())
+            .or_else(|| {
+                candidates.iter().find(|mv| {
+                    matches!(mv.kind(), MoveKind::Castling) && mv.target() == parsed.to()
+                })
+            })
+            .cloned()
+    }
+
+    fn parse_san(&self, pos: &Position, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+        let kingside = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(kingside) = kingside {
+            return self.generate_legal_moves(pos).find(|mv| {
+                if kingside {
+                    mv.is_king_castle()
+                } else {
+                    mv.is_queen_castle()
+                }
+            });
+        }
+
+        let (san, promotion) = match san.char_indices().last()? {
+            (idx, c) if "QRBN".contains(c) && idx > 0 => {
+                let promotion = Piece::from_str(&c.to_string()).ok()?;
+                (san[..idx].trim_end_matches('='), Some(promotion))
+            }
+            _ => (san, None),
+        };
+
+        let (piece, san) = match san.chars().next()? {
+            c if "KQRBN".contains(c) => (Piece::from_str(&c.to_string()).ok()?, &san[1..]),
+            _ => (Piece::Pawn, san),
+        };
+
+        let to = Square::from_str(san.get(san.len().checked_sub(2)?..)?).ok()?;
+        let mut file = None;
+        let mut rank = None;
+        for c in san[..san.len() - 2]
+            .chars()
+            .filter(|c| !matches!(c, 'x' | '-'))
+        {
+            match c {
+                'a'..='h' if file.is_none() => file = Some(File::try_from(c as u8 - b'a').ok()?),
+                '1'..='8' if rank.is_none() => rank = Some(Rank::try_from(c as u8 - b'1').ok()?),
+                _ => return None,
+            }
+        }
+
+        self.generate_legal_moves(pos)
+            .filter(|mv| {
+                mv.to() == to
+                    && mv.promotion() == promotion
+                    && pos.piece_at(&mv.from()).map(|(piece, _)| piece) == Some(piece)
+                    && file.is_none_or(|file| mv.from().file() == file)
+                    && rank.is_none_or(|rank| mv.from().rank() == rank)
+            })
+            .exactly_one()
+            .ok()
+    }
+
+    fn parse_move(&self, pos: &Position, mv: &str) -> Option<Move> {
+        self.parse_san(pos, mv).or_else(|| self.parse_uci(pos, mv))
+    }
+
     fn to_algebraic_notation(&self, pos: &Position, mv: &Move) -> String {
         let (piece, _) = pos.piece_at(&mv.from()).expect("No piece at from square.");
 
-        if piece == Piece::King
-            && matches!(mv.kind(), MoveKind::Castling)
-            && ((mv.from() == Square::E1 && mv.to() == Square::G1)
-                || (mv.from() == Square::E8 && mv.to() == Square::G8))
-        {
+        if piece == Piece::King && mv.is_king_castle() {
             return "O-O".to_string();
         }
-        if piece == Piece::King
-            && matches!(mv.kind(), MoveKind::Castling)
-            && ((mv.from() == Square::E1 && mv.to() == Square::C1)
-                || (mv.from() == Square::E8 && mv.to() == Square::C8))
-        {
+        if piece == Piece::King && mv.is_queen_castle() {
             return "O-O-O".to_string();
         }
 
@@ -93,7 +180,11 @@ impl ChessNotation for MoveGen {
             let mut cloned_pos = pos.clone();
             cloned_pos.make_move(mv).unwrap();
             if self.is_check(&cloned_pos) {
-                "+"
+                if self.generate_legal_moves(&cloned_pos).next().is_none() {
+                    "#"
+                } else {
+                    "+"
+                }
             } else {
                 ""
             }
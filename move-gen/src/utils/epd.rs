@@ -0,0 +1,60 @@
+use alloc::{string::String, vec::Vec};
+
+use anyhow::Result;
+use sdk::{epd::Epd, position::Position};
+
+use crate::{
+    generators::movegen::MoveGen,
+    r#move::{MakeMove, Move},
+    utils::chess_notation::ChessNotation,
+};
+
+/// Resolves the SAN or UCI move operands of EPD records against their position.
+pub trait EpdMoves {
+    /// Resolves moves that are all played in `pos`, as in `bm` and `am`.
+    fn resolve_moves(&self, pos: &Position, moves: &[String]) -> Result<Vec<Move>>;
+    /// Resolves moves played one after another from `pos`, as in `pv`.
+    fn resolve_variation(&self, pos: &Position, moves: &[String]) -> Result<Vec<Move>>;
+
+    /// Moves of the `bm` opcode, empty if the record has none.
+    fn best_moves(&self, epd: &Epd) -> Result<Vec<Move>> {
+        self.resolve_moves(&epd.position, epd.best_moves().unwrap_or_default())
+    }
+
+    /// Moves of the `am` opcode, empty if the record has none.
+    fn avoid_moves(&self, epd: &Epd) -> Result<Vec<Move>> {
+        self.resolve_moves(&epd.position, epd.avoid_moves().unwrap_or_default())
+    }
+
+    /// Moves of the `pv` opcode, empty if the record has none.
+    fn predicted_variation(&self, epd: &Epd) -> Result<Vec<Move>> {
+        self.resolve_variation(&epd.position, epd.predicted_variation().unwrap_or_default())
+    }
+}
+
+impl EpdMoves for MoveGen {
+    fn resolve_moves(&self, pos: &Position, moves: &[String]) -> Result<Vec<Move>> {
+        moves
+            .iter()
+            .map(|mv| {
+                self.parse_move(pos, mv)
+                    .ok_or_else(|| anyhow::anyhow!("Illegal or ambiguous move: '{mv}'"))
+            })
+            .collect()
+    }
+
+    fn resolve_variation(&self, pos: &Position, moves: &[String]) -> Result<Vec<Move>> {
+        let mut pos = pos.clone();
+        let mut variation = Vec::with_capacity(moves.len());
+
+        for mv in moves {
+            let resolved = self
+                .parse_move(&pos, mv)
+                .ok_or_else(|| anyhow::anyhow!("Illegal or ambiguous move: '{mv}'"))?;
+            pos.make_move(&resolved)?;
+            variation.push(resolved);
+        }
+
+        Ok(variation)
+    }
+}
@@ -1,2 +1,4 @@
+#[cfg(feature = "std")]
 pub mod logger;
 pub mod chess_notation;
+pub mod epd;
@@ -1,5 +1,5 @@
 #![allow(clippy::cast_possible_truncation)]
-use std::{
+use core::{
     fmt::{Debug, Display},
     ops::{
         Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Deref, Mul,
@@ -7,6 +7,7 @@ use std::{
     },
 };
 
+#[cfg(feature = "std")]
 use colored::Colorize;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
@@ -17,7 +18,7 @@ pub struct Bitboard(pub u64);
 
 pub const EMPTY: Bitboard = Bitboard(0);
 
-#[derive(IntoPrimitive, TryFromPrimitive)]
+#[derive(IntoPrimitive, TryFromPrimitive, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Direction {
     #[num_enum(default)]
@@ -31,9 +32,81 @@ pub enum Direction {
     SouthWest,
 }
 
+impl Direction {
+    #[must_use]
+    pub const fn all() -> [Direction; 8] {
+        [
+            Direction::North,
+            Direction::South,
+            Direction::East,
+            Direction::West,
+            Direction::NorthEast,
+            Direction::NorthWest,
+            Direction::SouthEast,
+            Direction::SouthWest,
+        ]
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::SouthWest => Direction::NorthEast,
+        }
+    }
+
+    /// Returns tuple of `(rank_offset, file_offset)` of a single step, as taken by
+    /// [`Square::offset`](crate::square::Square::offset).
+    #[must_use]
+    pub const fn offsets(self) -> (i8, i8) {
+        match self {
+            Direction::North => (1, 0),
+            Direction::South => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::West => (0, -1),
+            Direction::NorthEast => (1, 1),
+            Direction::NorthWest => (1, -1),
+            Direction::SouthEast => (-1, 1),
+            Direction::SouthWest => (-1, -1),
+        }
+    }
+
+    /// Returns a single step as a left rotation, together with the squares the step can land
+    /// on. Rotating keeps the fill direction agnostic, the mask drops bits wrapped around an edge.
+    const fn kogge_stone_step(self) -> (u32, u64) {
+        match self {
+            Direction::North => (8, 0xFFFF_FFFF_FFFF_FF00),
+            Direction::South => (64 - 8, 0x00FF_FFFF_FFFF_FFFF),
+            Direction::East => (1, 0xFEFE_FEFE_FEFE_FEFE),
+            Direction::West => (64 - 1, 0x7F7F_7F7F_7F7F_7F7F),
+            Direction::NorthEast => (9, 0xFEFE_FEFE_FEFE_FE00),
+            Direction::NorthWest => (7, 0x7F7F_7F7F_7F7F_7F00),
+            Direction::SouthEast => (64 - 7, 0x00FE_FEFE_FEFE_FEFE),
+            Direction::SouthWest => (64 - 9, 0x007F_7F7F_7F7F_7F7F),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_diagonal(self) -> bool {
+        matches!(
+            self,
+            Direction::NorthEast
+                | Direction::NorthWest
+                | Direction::SouthEast
+                | Direction::SouthWest
+        )
+    }
+}
+
 impl Bitboard {
     #[must_use]
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.0 == 0
     }
 
@@ -43,17 +116,15 @@ impl Bitboard {
     }
 
     #[must_use]
-    pub fn lsb(&self) -> Square {
-        (self.0.trailing_zeros() as u8)
-            .try_into()
-            .expect("BUG: LSB couldn't be computed from bitboard.")
+    pub const fn lsb(&self) -> Square {
+        assert!(self.0 != 0, "BUG: LSB couldn't be computed from bitboard.");
+        Square::from_index(self.0.trailing_zeros() as u8)
     }
 
     #[must_use]
-    pub fn msb(&self) -> Square {
-        (63 - self.0.leading_zeros() as u8)
-            .try_into()
-            .expect("BUG: MSB couldn't be computed from bitboard.")
+    pub const fn msb(&self) -> Square {
+        assert!(self.0 != 0, "BUG: MSB couldn't be computed from bitboard.");
+        Square::from_index(63 - self.0.leading_zeros() as u8)
     }
 
     pub fn pop_lsb(&mut self) -> Square {
@@ -65,23 +136,23 @@ impl Bitboard {
 
     pub fn pop_msb(&mut self) -> Square {
         let square = self.msb();
-        self.0 &= self.0 - 1;
+        self.0 ^= square.bitboard().0;
 
         square
     }
 
     #[must_use]
-    pub fn has(&self, square: Square) -> bool {
+    pub const fn has(&self, square: Square) -> bool {
         self.0 & square.bitboard().0 != 0
     }
 
     #[must_use]
-    pub fn count(&self) -> u8 {
+    pub const fn count(&self) -> u8 {
         self.0.count_ones() as u8
     }
 
     #[must_use]
-    pub fn shift(&self, direction: &Direction) -> Bitboard {
+    pub const fn shift(&self, direction: &Direction) -> Bitboard {
         const FILE_A: Bitboard = File::A.bitboard();
         const FILE_H: Bitboard = File::H.bitboard();
 
@@ -97,6 +168,109 @@ impl Bitboard {
         }
     }
 
+    /// Kogge-Stone occluded fill: extends every square of `self` in `direction` through the
+    /// `empty` squares. The result contains the starting squares, but not the blockers.
+    #[must_use]
+    pub const fn occluded_fill(&self, empty: Bitboard, direction: Direction) -> Bitboard {
+        let (shift, wrap) = direction.kogge_stone_step();
+
+        let mut gen = self.0;
+        let mut pro = empty.0 & wrap;
+        gen |= pro & gen.rotate_left(shift);
+        pro &= pro.rotate_left(shift);
+        gen |= pro & gen.rotate_left(2 * shift);
+        pro &= pro.rotate_left(2 * shift);
+        gen |= pro & gen.rotate_left(4 * shift);
+
+        Bitboard(gen)
+    }
+
+    /// Squares attacked in `direction` by sliders on every square of `self`, the first blocker
+    /// included.
+    #[must_use]
+    pub const fn sliding_attacks(&self, empty: Bitboard, direction: Direction) -> Bitboard {
+        let (shift, wrap) = direction.kogge_stone_step();
+        let fill = self.occluded_fill(empty, direction);
+
+        Bitboard(fill.0.rotate_left(shift) & wrap)
+    }
+
+    /// Union of the attacks of rooks on every square of `self`.
+    #[must_use]
+    pub const fn rook_attacks(&self, occupied: Bitboard) -> Bitboard {
+        let empty = Bitboard(!occupied.0);
+
+        Bitboard(
+            self.sliding_attacks(empty, Direction::North).0
+                | self.sliding_attacks(empty, Direction::South).0
+                | self.sliding_attacks(empty, Direction::East).0
+                | self.sliding_attacks(empty, Direction::West).0,
+        )
+    }
+
+    /// Union of the attacks of bishops on every square of `self`.
+    #[must_use]
+    pub const fn bishop_attacks(&self, occupied: Bitboard) -> Bitboard {
+        let empty = Bitboard(!occupied.0);
+
+        Bitboard(
+            self.sliding_attacks(empty, Direction::NorthEast).0
+                | self.sliding_attacks(empty, Direction::NorthWest).0
+                | self.sliding_attacks(empty, Direction::SouthEast).0
+                | self.sliding_attacks(empty, Direction::SouthWest).0,
+        )
+    }
+
+    /// Union of the attacks of queens on every square of `self`.
+    #[must_use]
+    pub const fn queen_attacks(&self, occupied: Bitboard) -> Bitboard {
+        Bitboard(self.rook_attacks(occupied).0 | self.bishop_attacks(occupied).0)
+    }
+
+    /// Mirrors the board across the horizontal axis, so rank 1 becomes rank 8.
+    #[must_use]
+    pub const fn flip_vertical(&self) -> Bitboard {
+        Bitboard(self.0.swap_bytes())
+    }
+
+    /// Mirrors the board across the vertical axis, so file A becomes file H.
+    #[must_use]
+    pub const fn mirror_horizontal(&self) -> Bitboard {
+        const K1: u64 = 0x5555_5555_5555_5555;
+        const K2: u64 = 0x3333_3333_3333_3333;
+        const K4: u64 = 0x0F0F_0F0F_0F0F_0F0F;
+
+        let mut x = self.0;
+        x = ((x >> 1) & K1) | ((x & K1) << 1);
+        x = ((x >> 2) & K2) | ((x & K2) << 2);
+        x = ((x >> 4) & K4) | ((x & K4) << 4);
+
+        Bitboard(x)
+    }
+
+    /// Mirrors the board across the A1-H8 diagonal, so A8 becomes H1.
+    #[must_use]
+    pub const fn flip_diagonal(&self) -> Bitboard {
+        const K1: u64 = 0x5500_5500_5500_5500;
+        const K2: u64 = 0x3333_0000_3333_0000;
+        const K4: u64 = 0x0F0F_0F0F_0000_0000;
+
+        let mut x = self.0;
+        let mut t = K4 & (x ^ (x << 28));
+        x ^= t ^ (t >> 28);
+        t = K2 & (x ^ (x << 14));
+        x ^= t ^ (t >> 14);
+        t = K1 & (x ^ (x << 7));
+        x ^= t ^ (t >> 7);
+
+        Bitboard(x)
+    }
+
+    #[must_use]
+    pub const fn rotate_180(&self) -> Bitboard {
+        Bitboard(self.0.reverse_bits())
+    }
+
     #[must_use]
     pub fn subsets(&self) -> SubsetIterator {
         SubsetIterator {
@@ -164,13 +338,15 @@ impl Iterator for BoardIterator {
 // DISPLAY
 
 impl Debug for Bitboard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if self.0 == 0 {
             return writeln!(f, "Empty bitboard.");
         }
         writeln!(f)?;
-        let zero = "0".green();
-        let one = "1".yellow();
+        #[cfg(feature = "std")]
+        let (zero, one) = ("0".green(), "1".yellow());
+        #[cfg(not(feature = "std"))]
+        let (zero, one) = ("0", "1");
         for rank in (0..8).rev() {
             write!(f, "{}   ", rank + 1)?;
             for file in 0..8 {
@@ -194,7 +370,7 @@ impl Debug for Bitboard {
 }
 
 impl Display for Bitboard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:#X}", self.0)
     }
 }
@@ -296,3 +472,105 @@ impl Not for Bitboard {
         Bitboard(!self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::Bitboard,
+        lookup::sliders::{mask_slider_attacks_occ, Slider},
+        square::Square,
+    };
+
+    const SAMPLES: [u64; 4] = [
+        0x0000_0000_0000_0001,
+        0x8000_0000_0000_00FF,
+        0x0123_4567_89AB_CDEF,
+        0xFFFF_0000_0000_FFFF,
+    ];
+
+    #[test]
+    fn test_transforms_map_squares() {
+        for square in Square::iter() {
+            let bb = square.bitboard();
+            let (file, rank) = (square as u8 % 8, square as u8 / 8);
+
+            assert_eq!(bb.flip_vertical(), Bitboard(1 << ((7 - rank) * 8 + file)));
+            assert_eq!(bb.mirror_horizontal(), Bitboard(1 << (rank * 8 + 7 - file)));
+            assert_eq!(bb.flip_diagonal(), Bitboard(1 << (file * 8 + rank)));
+            assert_eq!(bb.rotate_180(), Bitboard(1 << (63 - square as u8)));
+            assert_eq!(bb.flip_vertical(), square.flip_vertical().bitboard());
+            assert_eq!(
+                bb.mirror_horizontal(),
+                square.mirror_horizontal().bitboard()
+            );
+        }
+    }
+
+    #[test]
+    fn test_transforms_are_involutions() {
+        for bb in SAMPLES.map(Bitboard) {
+            assert_eq!(bb.flip_vertical().flip_vertical(), bb);
+            assert_eq!(bb.mirror_horizontal().mirror_horizontal(), bb);
+            assert_eq!(bb.flip_diagonal().flip_diagonal(), bb);
+            assert_eq!(bb.rotate_180().rotate_180(), bb);
+            assert_eq!(bb.flip_vertical().mirror_horizontal(), bb.rotate_180());
+        }
+    }
+
+    #[test]
+    fn test_kogge_stone_matches_ray_walk() {
+        for occ in SAMPLES.map(Bitboard) {
+            for square in Square::iter() {
+                let bb = square.bitboard();
+
+                assert_eq!(
+                    bb.rook_attacks(occ),
+                    mask_slider_attacks_occ(Slider::Rook, occ, square),
+                    "{square} {occ}"
+                );
+                assert_eq!(
+                    bb.bishop_attacks(occ),
+                    mask_slider_attacks_occ(Slider::Bishop, occ, square),
+                    "{square} {occ}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_kogge_stone_setwise() {
+        for occ in SAMPLES.map(Bitboard) {
+            let sliders = Bitboard(0x0000_0042_1800_0081);
+
+            let rooks = sliders.into_iter().fold(Bitboard::empty(), |acc, sq| {
+                acc | sq.bitboard().rook_attacks(occ)
+            });
+            let bishops = sliders.into_iter().fold(Bitboard::empty(), |acc, sq| {
+                acc | sq.bitboard().bishop_attacks(occ)
+            });
+
+            assert_eq!(sliders.rook_attacks(occ), rooks);
+            assert_eq!(sliders.bishop_attacks(occ), bishops);
+            assert_eq!(sliders.queen_attacks(occ), rooks | bishops);
+        }
+    }
+
+    #[test]
+    fn test_const_squares() {
+        const LSB: Square = Bitboard(0x0000_0042_1800_0080).lsb();
+        const MSB: Square = Bitboard(0x0000_0042_1800_0080).msb();
+        const OFFSET: Option<Square> = Square::E4.offset(2, -1);
+
+        assert_eq!((LSB, MSB), (Square::H1, Square::G5));
+        assert_eq!(OFFSET, Some(Square::D6));
+        assert_eq!(Square::H8.offset(0, 1), None);
+        for square in Square::iter() {
+            assert_eq!(Square::from_index(square as u8), square);
+        }
+
+        let mut bb = Bitboard(0x0000_0042_1800_0080);
+        assert_eq!(bb.pop_msb(), Square::G5);
+        assert_eq!(bb.pop_msb(), Square::B5);
+        assert_eq!(bb, Bitboard(0x0000_0000_1800_0080));
+    }
+}
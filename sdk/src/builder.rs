@@ -0,0 +1,203 @@
+use crate::{
+    position::{Castling, CastlingKind, Color, Piece, Position},
+    square::Square,
+    validation::InvalidPosition,
+};
+
+/// Edits a position piece by piece, e.g. for a setup mode or test fixtures.
+///
+/// Setters can be called in any order. [`PositionBuilder::build`] drops castling rights and the
+/// en passant square if the pieces don't support them, then validates the result. Like a double
+/// push, the en passant square is only kept if a pawn can take on it.
+#[derive(Debug, Clone)]
+pub struct PositionBuilder {
+    position: Position,
+}
+
+impl PositionBuilder {
+    #[must_use]
+    pub fn empty() -> Self {
+        PositionBuilder {
+            position: Position::empty(),
+        }
+    }
+
+    /// Puts a piece on the square, replacing the piece already there.
+    pub fn put(&mut self, square: Square, piece: Piece, color: Color) -> &mut Self {
+        self.position.remove_piece_at(&square);
+        self.position
+            .add_piece_at(square, piece, color)
+            .expect("BUG: Square was just cleared");
+        self
+    }
+
+    pub fn clear(&mut self, square: Square) -> &mut Self {
+        self.position.remove_piece_at(&square);
+        self
+    }
+
+    #[must_use]
+    pub fn piece_at(&self, square: Square) -> Option<(Piece, Color)> {
+        self.position.piece_at(&square)
+    }
+
+    pub fn side_to_move(&mut self, color: Color) -> &mut Self {
+        self.position.turn = color;
+        self
+    }
+
+    pub fn castling(&mut self, castling: Castling) -> &mut Self {
+        self.position.castling = castling;
+        self
+    }
+
+    pub fn en_passant(&mut self, square: Option<Square>) -> &mut Self {
+        self.position.en_passant = square;
+        self
+    }
+
+    pub fn halfmove_clock(&mut self, halfmove_clock: u16) -> &mut Self {
+        self.position.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    /// Sets the fullmove number, 0 is treated as 1.
+    pub fn fullmove_number(&mut self, fullmove_number: u16) -> &mut Self {
+        self.position.fullmove_number = fullmove_number.max(1);
+        self
+    }
+
+    pub fn build(&self) -> Result<Position, InvalidPosition> {
+        let mut position = self.position.clone();
+
+        for kind in CastlingKind::all() {
+            let Some(rook_square) = position.castling.rook_square(&kind) else {
+                continue;
+            };
+            let color = kind.color();
+
+            if position.piece_at(&rook_square) != Some((Piece::Rook, color))
+                || position.castling_kind_for_rook(rook_square, color) != Some(kind)
+            {
+                position.castling.remove_castling_kind(&kind);
+            }
+        }
+
+        if let Some(square) = position.en_passant {
+            if !position.is_valid_en_passant(square) || !position.can_take_en_passant(square) {
+                position.en_passant = None;
+            }
+        }
+
+        position.refresh_hashes();
+        position.validate()?;
+
+        Ok(position)
+    }
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl From<&Position> for PositionBuilder {
+    fn from(position: &Position) -> Self {
+        PositionBuilder {
+            position: position.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builder::PositionBuilder,
+        fen::Fen,
+        position::{Castling, Color, Piece, Position},
+        square::Square,
+        validation::ValidationError,
+    };
+
+    #[test]
+    fn test_build() {
+        let pos = PositionBuilder::empty()
+            .put(Square::E1, Piece::King, Color::White)
+            .put(Square::H1, Piece::Rook, Color::White)
+            .put(Square::E8, Piece::King, Color::Black)
+            .put(Square::D5, Piece::Pawn, Color::Black)
+            .put(Square::E5, Piece::Pawn, Color::White)
+            .side_to_move(Color::White)
+            .castling(Castling::full())
+            .en_passant(Some(Square::D6))
+            .halfmove_clock(0)
+            .fullmove_number(30)
+            .build()
+            .unwrap();
+
+        assert_eq!(pos.to_fen(), "4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 30");
+        assert_eq!(
+            pos,
+            Position::from_fen("4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 30".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_en_passant_without_capture() {
+        let pos = PositionBuilder::empty()
+            .put(Square::E1, Piece::King, Color::White)
+            .put(Square::E8, Piece::King, Color::Black)
+            .put(Square::D5, Piece::Pawn, Color::Black)
+            .put(Square::F5, Piece::Pawn, Color::White)
+            .en_passant(Some(Square::D6))
+            .build()
+            .unwrap();
+
+        // The same position reached by d7d5 has no en passant square.
+        let played = Position::from_fen("4k3/8/8/3p1P2/8/8/8/4K3 w - - 0 1".to_string()).unwrap();
+        assert_eq!(pos.en_passant, None);
+        assert_eq!(pos.hash, played.hash);
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut builder = PositionBuilder::from(&Position::default());
+        builder
+            .clear(Square::E2)
+            .put(Square::E4, Piece::Pawn, Color::White)
+            .clear(Square::D7)
+            .put(Square::D4, Piece::Pawn, Color::Black)
+            .put(Square::A1, Piece::Knight, Color::White)
+            .side_to_move(Color::Black)
+            .en_passant(Some(Square::E3));
+
+        assert_eq!(
+            builder.piece_at(Square::A1),
+            Some((Piece::Knight, Color::White))
+        );
+        assert_eq!(
+            builder.build().unwrap().to_fen(),
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/NNBQKBNR b Kkq e3 0 1"
+        );
+
+        builder.en_passant(Some(Square::D3));
+        assert_eq!(builder.build().unwrap().en_passant, None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let err = PositionBuilder::empty()
+            .put(Square::E1, Piece::King, Color::White)
+            .put(Square::E8, Piece::Pawn, Color::Black)
+            .build()
+            .unwrap_err();
+
+        assert!(err
+            .errors
+            .contains(&ValidationError::MissingKing(Color::Black)));
+        assert!(err
+            .errors
+            .contains(&ValidationError::PawnOnBackRank(Square::E8)));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    position::{CastlingKind, Color, Piece, Position},
+    square::{File, Rank, Square},
+};
+
+/// Number of Chess960 starting positions.
+pub const CHESS960_POSITIONS: u16 = 960;
+
+/// Squares of the two knights among the five files left after placing bishops and queen.
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+impl Position {
+    /// Chess960 starting position number `index` in the standard (Scharnagl) numbering, with
+    /// full castling rights. Position 518 is the standard starting position.
+    #[must_use]
+    pub fn chess960(index: u16) -> Position {
+        assert!(
+            index < CHESS960_POSITIONS,
+            "Chess960 position index out of range: {index}"
+        );
+
+        let back_rank = chess960_back_rank(index as usize);
+
+        let mut position = Position::empty();
+        for (file, piece) in File::ALL.into_iter().zip(back_rank) {
+            for (color, back, pawns) in [
+                (Color::White, Rank::R1, Rank::R2),
+                (Color::Black, Rank::R8, Rank::R7),
+            ] {
+                position
+                    .add_piece_at(Square::from((file, back)), piece, color)
+                    .expect("BUG: Square visited twice");
+                position
+                    .add_piece_at(Square::from((file, pawns)), Piece::Pawn, color)
+                    .expect("BUG: Square visited twice");
+            }
+        }
+
+        let king_file = File::ALL[back_rank
+            .iter()
+            .position(|&piece| piece == Piece::King)
+            .expect("BUG: Back rank without king")];
+        for (file, piece) in File::ALL.into_iter().zip(back_rank) {
+            if piece == Piece::Rook {
+                for color in Color::iter() {
+                    let kind = CastlingKind::new(color, file > king_file);
+                    position.castling.add_castling_rook(&kind, file);
+                }
+            }
+        }
+
+        position.refresh_hashes();
+        position
+    }
+}
+
+fn chess960_back_rank(mut index: usize) -> [Piece; 8] {
+    let mut back_rank = [None; 8];
+
+    // Light squared bishop on b, d, f or h, dark squared bishop on a, c, e or g.
+    back_rank[index % 4 * 2 + 1] = Some(Piece::Bishop);
+    index /= 4;
+    back_rank[index % 4 * 2] = Some(Piece::Bishop);
+    index /= 4;
+
+    let mut place_on_empty = |nth: usize, piece: Piece| {
+        let file = (0..8)
+            .filter(|&file| back_rank[file].is_none())
+            .nth(nth)
+            .expect("BUG: Not enough empty files");
+        back_rank[file] = Some(piece);
+    };
+
+    place_on_empty(index % 6, Piece::Queen);
+    index /= 6;
+
+    // Placing the first knight shifts the files of the second one.
+    let (first, second) = KNIGHT_PLACEMENTS[index];
+    place_on_empty(first, Piece::Knight);
+    place_on_empty(second - 1, Piece::Knight);
+
+    // The king stands between the rooks on the three files left.
+    place_on_empty(0, Piece::Rook);
+    place_on_empty(0, Piece::King);
+    place_on_empty(0, Piece::Rook);
+
+    back_rank.map(|piece| piece.expect("BUG: Back rank not filled"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        chess960::CHESS960_POSITIONS,
+        fen::Fen,
+        position::{CastlingKind, Position},
+        square::File,
+    };
+
+    #[test]
+    fn test_chess960_positions() {
+        assert_eq!(Position::chess960(518), Position::default());
+        assert_eq!(
+            Position::chess960(0).to_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert_eq!(
+            Position::chess960(959).to_shredder_fen(),
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w CAca - 0 1"
+        );
+
+        let mut placements = HashSet::new();
+        for index in 0..CHESS960_POSITIONS {
+            let position = Position::chess960(index);
+            assert_eq!(position.validate(), Ok(()), "{index}");
+            assert!(placements.insert(position.to_fen()), "{index}");
+        }
+
+        let position = Position::chess960(0);
+        assert_eq!(
+            position.castling.rook_file(&CastlingKind::BlackQueenside),
+            Some(File::F)
+        );
+    }
+}
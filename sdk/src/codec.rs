@@ -0,0 +1,425 @@
+//! Fixed-size binary encoding of positions, meant for storing large numbers of them.
+//!
+//! A packed position takes [`PACKED_SIZE`] bytes:
+//!
+//! | bytes    | content                                                    |
+//! |----------|------------------------------------------------------------|
+//! | `0..8`   | occupancy bitboard, little endian                          |
+//! | `8..24`  | 4-bit code of every occupied square, in square order       |
+//! | `24..26` | halfmove clock, little endian                              |
+//! | `26..28` | fullmove number, little endian                             |
+//! | `28..32` | reserved, always zero                                      |
+//!
+//! Codes `0..12` are `color * 6 + piece`. The remaining codes carry the rest of the state:
+//! a pawn that can be captured en passant, a rook that can still castle (one code per color)
+//! and the black king when black is to move. The side of a castling rook is given by the king,
+//! so Chess960 rights are encoded the same way.
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use crate::{
+    bitboard::Bitboard,
+    position::{CastlingKind, Color, Piece, Position},
+    square::{File, Rank, Square},
+    validation::InvalidPosition,
+};
+
+pub const PACKED_SIZE: usize = 32;
+
+const EN_PASSANT_PAWN: u8 = 12;
+const WHITE_CASTLING_ROOK: u8 = 13;
+const BLACK_CASTLING_ROOK: u8 = 14;
+const BLACK_KING_TO_MOVE: u8 = 15;
+
+#[derive(Debug)]
+pub enum CodecError {
+    TooManyPieces(u8),
+    CastlingWithoutRook(CastlingKind),
+    EnPassantWithoutPawn(Square),
+    InvalidCode {
+        square: Square,
+        code: u8,
+    },
+    InconsistentTurn,
+    /// Black is to move but doesn't have exactly one king to carry the turn.
+    TurnWithoutKing,
+    NonZeroPadding,
+    InvalidPosition(InvalidPosition),
+    #[cfg(feature = "std")]
+    Io(io::Error),
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CodecError::TooManyPieces(count) => {
+                write!(f, "{count} pieces don't fit in a packed position")
+            }
+            CodecError::CastlingWithoutRook(kind) => {
+                write!(f, "{kind:?} castling right without rook on its home square")
+            }
+            CodecError::EnPassantWithoutPawn(square) => {
+                write!(
+                    f,
+                    "En passant square {square} without a pawn in front of it"
+                )
+            }
+            CodecError::InvalidCode { square, code } => {
+                write!(f, "Invalid code {code} at {square}")
+            }
+            CodecError::InconsistentTurn => {
+                write!(f, "En passant pawn doesn't match the side to move")
+            }
+            CodecError::TurnWithoutKing => {
+                write!(f, "Black to move needs exactly one black king")
+            }
+            CodecError::NonZeroPadding => write!(f, "Unused bits are not zero"),
+            CodecError::InvalidPosition(err) => write!(f, "{err}"),
+            #[cfg(feature = "std")]
+            CodecError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl core::error::Error for CodecError {}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for CodecError {
+    fn from(value: io::Error) -> Self {
+        CodecError::Io(value)
+    }
+}
+
+impl Position {
+    /// Packs the position into [`PACKED_SIZE`] bytes. Fails if the state can't be represented,
+    /// e.g. a castling right without its rook or black to move without a black king.
+    pub fn pack(&self) -> Result<[u8; PACKED_SIZE], CodecError> {
+        let count = self.occupied.count();
+        if count > 32 {
+            return Err(CodecError::TooManyPieces(count));
+        }
+
+        let mut codes = [0u8; 64];
+        for (square, piece, color) in self.pieces() {
+            codes[square as usize] = color as u8 * 6 + piece as u8;
+        }
+
+        for kind in CastlingKind::all() {
+            let Some(rook_square) = self.castling.rook_square(&kind) else {
+                continue;
+            };
+
+            if self.piece_at(&rook_square) != Some((Piece::Rook, kind.color())) {
+                return Err(CodecError::CastlingWithoutRook(kind));
+            }
+
+            codes[rook_square as usize] = match kind.color() {
+                Color::White => WHITE_CASTLING_ROOK,
+                Color::Black => BLACK_CASTLING_ROOK,
+            };
+        }
+
+        if let Some(square) = self.en_passant {
+            let pushed = square
+                .offset(if self.turn == Color::White { -1 } else { 1 }, 0)
+                .filter(|pushed| self.piece_at(pushed) == Some((Piece::Pawn, self.enemy())))
+                .ok_or(CodecError::EnPassantWithoutPawn(square))?;
+
+            codes[pushed as usize] = EN_PASSANT_PAWN;
+        }
+
+        if self.turn == Color::Black {
+            let king = self.pieces[Color::Black as usize][Piece::King as usize];
+            if king.count() != 1 {
+                return Err(CodecError::TurnWithoutKing);
+            }
+            codes[king.lsb() as usize] = BLACK_KING_TO_MOVE;
+        }
+
+        let mut bytes = [0u8; PACKED_SIZE];
+        bytes[0..8].copy_from_slice(&self.occupied.0.to_le_bytes());
+        for (idx, square) in self.occupied.into_iter().enumerate() {
+            bytes[8 + idx / 2] |= codes[square as usize] << (4 * (idx % 2));
+        }
+        bytes[24..26].copy_from_slice(&self.halfmove_clock.to_le_bytes());
+        bytes[26..28].copy_from_slice(&self.fullmove_number.to_le_bytes());
+
+        Ok(bytes)
+    }
+
+    /// Unpacks a position produced by [`Position::pack`]. The result is checked with
+    /// [`Position::validate`], so corrupted input is rejected rather than decoded into a position
+    /// `MoveGen` can't handle.
+    pub fn unpack(bytes: &[u8; PACKED_SIZE]) -> Result<Position, CodecError> {
+        let occupied = Bitboard(u64::from_le_bytes(bytes[0..8].try_into().unwrap()));
+        if occupied.count() > 32 {
+            return Err(CodecError::TooManyPieces(occupied.count()));
+        }
+
+        let count = usize::from(occupied.count());
+        let code = |idx: usize| (bytes[8 + idx / 2] >> (4 * (idx % 2))) & 0b1111;
+        let unused_codes = (count..32).any(|idx| code(idx) != 0);
+        if unused_codes || bytes[28..32].iter().any(|&byte| byte != 0) {
+            return Err(CodecError::NonZeroPadding);
+        }
+
+        let mut position = Position::empty();
+        let mut black_to_move = false;
+        let mut en_passant_pawn = None;
+        let mut castling_rooks = Vec::new();
+
+        for (idx, square) in occupied.into_iter().enumerate() {
+            let (piece, color) = match code(idx) {
+                code @ 0..=11 => (
+                    Piece::try_from(usize::from(code % 6)).unwrap(),
+                    if code < 6 { Color::White } else { Color::Black },
+                ),
+                EN_PASSANT_PAWN if en_passant_pawn.is_none() => {
+                    let color = match square.rank() {
+                        Rank::R4 => Color::White,
+                        Rank::R5 => Color::Black,
+                        _ => {
+                            return Err(CodecError::InvalidCode {
+                                square,
+                                code: EN_PASSANT_PAWN,
+                            })
+                        }
+                    };
+                    en_passant_pawn = Some((square, color));
+                    (Piece::Pawn, color)
+                }
+                code @ (WHITE_CASTLING_ROOK | BLACK_CASTLING_ROOK) => {
+                    let color = if code == WHITE_CASTLING_ROOK {
+                        Color::White
+                    } else {
+                        Color::Black
+                    };
+                    // Resolved once the king is placed.
+                    castling_rooks.push((square, color, code));
+                    (Piece::Rook, color)
+                }
+                BLACK_KING_TO_MOVE if !black_to_move => {
+                    black_to_move = true;
+                    (Piece::King, Color::Black)
+                }
+                code => return Err(CodecError::InvalidCode { square, code }),
+            };
+
+            position
+                .add_piece_at(square, piece, color)
+                .expect("BUG: Occupied squares are distinct");
+        }
+
+        // Without a king on the back rank the rights are kept as in standard chess, so that
+        // validation reports them.
+        for (square, color, code) in castling_rooks {
+            let kind = position
+                .castling_kind_for_rook(square, color)
+                .unwrap_or(CastlingKind::new(color, square.file() > File::E));
+            if position.castling.has_castling_kind(&kind) {
+                return Err(CodecError::InvalidCode { square, code });
+            }
+            position.castling.add_castling_rook(&kind, square.file());
+        }
+
+        position.turn = if black_to_move {
+            Color::Black
+        } else {
+            Color::White
+        };
+
+        if let Some((square, color)) = en_passant_pawn {
+            if color == position.turn {
+                return Err(CodecError::InconsistentTurn);
+            }
+
+            let direction = if color == Color::White { -1 } else { 1 };
+            position.en_passant = square.offset(direction, 0);
+        }
+
+        position.halfmove_clock = u16::from_le_bytes([bytes[24], bytes[25]]);
+        position.fullmove_number = u16::from_le_bytes([bytes[26], bytes[27]]);
+
+        position.refresh_hashes();
+        position.validate().map_err(CodecError::InvalidPosition)?;
+
+        Ok(position)
+    }
+}
+
+/// Writes packed positions back to back.
+#[cfg(feature = "std")]
+pub struct PositionWriter<W: Write> {
+    inner: W,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> PositionWriter<W> {
+    pub fn new(inner: W) -> Self {
+        PositionWriter { inner }
+    }
+
+    pub fn write(&mut self, position: &Position) -> Result<(), CodecError> {
+        self.inner.write_all(&position.pack()?)?;
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads positions written by [`PositionWriter`]. Iteration ends at the end of the stream and
+/// yields an error if it ends in the middle of a position.
+#[cfg(feature = "std")]
+pub struct PositionReader<R: Read> {
+    inner: R,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> PositionReader<R> {
+    pub fn new(inner: R) -> Self {
+        PositionReader { inner }
+    }
+
+    pub fn read(&mut self) -> Result<Option<Position>, CodecError> {
+        let mut bytes = [0u8; PACKED_SIZE];
+        let mut filled = 0;
+        while filled < PACKED_SIZE {
+            match self.inner.read(&mut bytes[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Position::unpack(&bytes).map(Some)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Iterator for PositionReader<R> {
+    type Item = Result<Position, CodecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codec::{CodecError, PositionReader, PositionWriter, EN_PASSANT_PAWN, PACKED_SIZE},
+        fen::Fen,
+        position::{Color, Position},
+    };
+
+    const FENS: [&str; 6] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Qk - 17 42",
+        "8/8/8/3pP3/8/8/8/k1K5 w - d6 0 60",
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9",
+    ];
+
+    #[test]
+    fn test_round_trip() {
+        for fen in FENS {
+            let pos = Position::from_fen(fen.to_string()).unwrap();
+            let unpacked = Position::unpack(&pos.pack().unwrap()).unwrap();
+
+            assert_eq!(unpacked, pos, "{fen}");
+            assert_eq!(unpacked.to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_unrepresentable_positions() {
+        let pos = Position::from_fen("8/8/8/8/8/8/8/k1K4R w Q - 0 1".to_string()).unwrap();
+        assert!(matches!(
+            pos.pack(),
+            Err(CodecError::CastlingWithoutRook(_))
+        ));
+
+        let pos = Position::from_fen("8/8/8/8/8/8/8/k1K5 w - e6 0 1".to_string()).unwrap();
+        assert!(matches!(
+            pos.pack(),
+            Err(CodecError::EnPassantWithoutPawn(_))
+        ));
+
+        // The turn is stored on the black king, it must not be dropped silently.
+        for fen in [
+            "8/8/8/8/8/8/8/2K5 b - - 0 1",
+            "k7/8/8/8/8/8/8/k1K5 b - - 0 1",
+        ] {
+            let pos = Position::from_fen(fen.to_string()).unwrap();
+            assert!(
+                matches!(pos.pack(), Err(CodecError::TurnWithoutKing)),
+                "{fen}"
+            );
+        }
+        let pos = Position::from_fen("k7/8/8/8/8/8/8/2K5 b - - 0 1".to_string()).unwrap();
+        let unpacked = Position::unpack(&pos.pack().unwrap()).unwrap();
+        assert_eq!(unpacked.turn, Color::Black);
+        assert_eq!(unpacked, pos);
+    }
+
+    #[test]
+    fn test_rejects_corrupted_input() {
+        let bytes = Position::default().pack().unwrap();
+
+        let mut padding = bytes;
+        padding[31] = 1;
+        assert!(matches!(
+            Position::unpack(&padding),
+            Err(CodecError::NonZeroPadding)
+        ));
+
+        // Replace the white king with a pawn.
+        let mut no_king = bytes;
+        no_king[10] &= 0b1111_0000;
+        assert!(matches!(
+            Position::unpack(&no_king),
+            Err(CodecError::InvalidPosition(_))
+        ));
+
+        // Mark the a2 pawn as capturable en passant.
+        let mut en_passant = bytes;
+        en_passant[12] = (en_passant[12] & 0b1111_0000) | EN_PASSANT_PAWN;
+        assert!(matches!(
+            Position::unpack(&en_passant),
+            Err(CodecError::InvalidCode { .. })
+        ));
+    }
+
+    #[test]
+    fn test_stream() {
+        let positions = FENS
+            .iter()
+            .map(|fen| Position::from_fen((*fen).to_string()).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut writer = PositionWriter::new(Vec::new());
+        for pos in &positions {
+            writer.write(pos).unwrap();
+        }
+        let bytes = writer.into_inner();
+        assert_eq!(bytes.len(), positions.len() * PACKED_SIZE);
+
+        let read = PositionReader::new(bytes.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, positions);
+
+        let truncated = PositionReader::new(&bytes[..PACKED_SIZE + 3]).collect::<Vec<_>>();
+        assert_eq!(truncated.len(), 2);
+        assert!(matches!(truncated[1], Err(CodecError::Io(_))));
+    }
+}
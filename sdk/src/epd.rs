@@ -0,0 +1,501 @@
+//! Extended Position Description: the first four FEN fields followed by `opcode operands;`
+//! operations, e.g. `r1b1k2r/... w kq - bm Nxe5; id "test 1";`.
+//!
+//! Move operands are kept as written, SAN or UCI. The sdk knows no move generation, resolve them
+//! against [`Epd::position`] with the `move_gen` crate.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt::Display, str::FromStr};
+
+use crate::{
+    fen::{Fen, FenError},
+    position::Position,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// `bm`, best moves.
+    BestMoves(Vec<String>),
+    /// `am`, moves to avoid.
+    AvoidMoves(Vec<String>),
+    /// `id`, name of the record.
+    Id(String),
+    /// `c0` to `c9`, comments.
+    Comment(u8, String),
+    /// `acd`, analysis depth in plies.
+    AnalysisDepth(u32),
+    /// `ce`, centipawn evaluation from the point of view of the side to move.
+    CentipawnEvaluation(i32),
+    /// `pv`, predicted variation, each move played in the position after the previous one.
+    PredictedVariation(Vec<String>),
+    /// `hmvc`, halfmove clock.
+    HalfmoveClock(u16),
+    /// `fmvn`, fullmove number.
+    FullmoveNumber(u16),
+    /// `D1`, `D2`, ..., number of leaf nodes of the perft of the given depth.
+    Perft { depth: u8, nodes: u64 },
+    Other {
+        opcode: String,
+        operands: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epd {
+    /// Clocks are taken from `hmvc` and `fmvn`, or the optional FEN clock fields written by some
+    /// perft suites, and default to `0` and `1`.
+    pub position: Position,
+    /// Operations in the order they were read.
+    pub operations: Vec<Operation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    Fen(FenError),
+    InvalidOpcode(String),
+    InvalidOperands {
+        opcode: String,
+        operands: Vec<String>,
+    },
+    UnterminatedString,
+}
+
+impl Display for EpdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EpdError::Fen(err) => write!(f, "{err}"),
+            EpdError::InvalidOpcode(opcode) => write!(f, "Invalid EPD opcode '{opcode}'"),
+            EpdError::InvalidOperands { opcode, operands } => {
+                write!(f, "Invalid operands of '{opcode}': {operands:?}")
+            }
+            EpdError::UnterminatedString => write!(f, "Unterminated string operand"),
+        }
+    }
+}
+
+impl core::error::Error for EpdError {}
+
+impl From<FenError> for EpdError {
+    fn from(err: FenError) -> Self {
+        EpdError::Fen(err)
+    }
+}
+
+impl Operation {
+    #[must_use]
+    pub fn opcode(&self) -> String {
+        match self {
+            Operation::BestMoves(_) => "bm".to_string(),
+            Operation::AvoidMoves(_) => "am".to_string(),
+            Operation::Id(_) => "id".to_string(),
+            Operation::Comment(idx, _) => format!("c{idx}"),
+            Operation::AnalysisDepth(_) => "acd".to_string(),
+            Operation::CentipawnEvaluation(_) => "ce".to_string(),
+            Operation::PredictedVariation(_) => "pv".to_string(),
+            Operation::HalfmoveClock(_) => "hmvc".to_string(),
+            Operation::FullmoveNumber(_) => "fmvn".to_string(),
+            Operation::Perft { depth, .. } => format!("D{depth}"),
+            Operation::Other { opcode, .. } => opcode.clone(),
+        }
+    }
+
+    fn parse(opcode: String, operands: Vec<String>) -> Result<Operation, EpdError> {
+        let mut chars = opcode.chars();
+        let valid_opcode = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_opcode {
+            return Err(EpdError::InvalidOpcode(opcode));
+        }
+
+        let moves = || {
+            if operands.is_empty() {
+                Err(invalid_operands(&opcode, &operands))
+            } else {
+                Ok(operands.clone())
+            }
+        };
+
+        let operation = match opcode.as_str() {
+            "bm" => Operation::BestMoves(moves()?),
+            "am" => Operation::AvoidMoves(moves()?),
+            "pv" => Operation::PredictedVariation(moves()?),
+            "id" => Operation::Id(single(&opcode, &operands)?),
+            "acd" => Operation::AnalysisDepth(number(&opcode, &operands)?),
+            "ce" => Operation::CentipawnEvaluation(number(&opcode, &operands)?),
+            "hmvc" => Operation::HalfmoveClock(number(&opcode, &operands)?),
+            "fmvn" => Operation::FullmoveNumber(number(&opcode, &operands)?),
+            _ => {
+                if let Some(idx) = comment_index(&opcode) {
+                    Operation::Comment(idx, single(&opcode, &operands)?)
+                } else if let Some(depth) = perft_depth(&opcode) {
+                    Operation::Perft {
+                        depth,
+                        nodes: number(&opcode, &operands)?,
+                    }
+                } else {
+                    Operation::Other { opcode, operands }
+                }
+            }
+        };
+
+        Ok(operation)
+    }
+}
+
+fn invalid_operands(opcode: &str, operands: &[String]) -> EpdError {
+    EpdError::InvalidOperands {
+        opcode: opcode.to_string(),
+        operands: operands.to_vec(),
+    }
+}
+
+fn single(opcode: &str, operands: &[String]) -> Result<String, EpdError> {
+    match operands {
+        [operand] => Ok(operand.clone()),
+        _ => Err(invalid_operands(opcode, operands)),
+    }
+}
+
+fn number<T: FromStr>(opcode: &str, operands: &[String]) -> Result<T, EpdError> {
+    single(opcode, operands)?
+        .parse()
+        .map_err(|_| invalid_operands(opcode, operands))
+}
+
+fn comment_index(opcode: &str) -> Option<u8> {
+    match opcode.as_bytes() {
+        [b'c', digit @ b'0'..=b'9'] => Some(digit - b'0'),
+        _ => None,
+    }
+}
+
+fn perft_depth(opcode: &str) -> Option<u8> {
+    let depth = opcode.strip_prefix('D')?;
+    if !depth.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    depth.parse().ok().filter(|depth| *depth > 0)
+}
+
+impl Epd {
+    #[must_use]
+    pub fn new(position: Position) -> Self {
+        Epd {
+            position,
+            operations: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        self.operations.iter().find_map(|op| match op {
+            Operation::Id(id) => Some(id.as_str()),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn comment(&self, idx: u8) -> Option<&str> {
+        self.operations.iter().find_map(|op| match op {
+            Operation::Comment(comment_idx, comment) if *comment_idx == idx => {
+                Some(comment.as_str())
+            }
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn best_moves(&self) -> Option<&[String]> {
+        self.operations.iter().find_map(|op| match op {
+            Operation::BestMoves(moves) => Some(moves.as_slice()),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn avoid_moves(&self) -> Option<&[String]> {
+        self.operations.iter().find_map(|op| match op {
+            Operation::AvoidMoves(moves) => Some(moves.as_slice()),
+            _ => None,
+        })
+    }
+
+    #[must_use]
+    pub fn predicted_variation(&self) -> Option<&[String]> {
+        self.operations.iter().find_map(|op| match op {
+            Operation::PredictedVariation(moves) => Some(moves.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Expected number of leaf nodes at `depth`, from the `D<depth>` opcode.
+    #[must_use]
+    pub fn perft(&self, depth: u8) -> Option<u64> {
+        self.operations.iter().find_map(|op| match op {
+            Operation::Perft {
+                depth: perft_depth,
+                nodes,
+            } if *perft_depth == depth => Some(*nodes),
+            _ => None,
+        })
+    }
+}
+
+/// Splits the operations into opcodes and operands. Double quoted operands may contain
+/// whitespace and semicolons, the quotes are removed.
+fn tokenize(operations: &str) -> Result<Vec<Vec<String>>, EpdError> {
+    let mut result = Vec::new();
+    let mut tokens = Vec::new();
+    let mut token = None::<String>;
+    let mut quoted = false;
+
+    for c in operations.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                token.get_or_insert_with(String::new);
+            }
+            c if quoted => token.get_or_insert_with(String::new).push(c),
+            ';' => {
+                tokens.extend(token.take());
+                if !tokens.is_empty() {
+                    result.push(core::mem::take(&mut tokens));
+                }
+            }
+            c if c.is_whitespace() => tokens.extend(token.take()),
+            c => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quoted {
+        return Err(EpdError::UnterminatedString);
+    }
+
+    // The last semicolon is often missing.
+    tokens.extend(token);
+    if !tokens.is_empty() {
+        result.push(tokens);
+    }
+
+    Ok(result)
+}
+
+impl FromStr for Epd {
+    type Err = EpdError;
+
+    /// Parses a single EPD record. Perft suites such as `perftsuite.epd` also write the FEN
+    /// clock fields, these are accepted before the first operation. Positions failing
+    /// [`Position::validate`] are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rest = s.trim();
+        let mut fields = Vec::new();
+        for _ in 0..4 {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+
+        let mut clocks = Vec::new();
+        while let Some(clock) = rest.split_whitespace().next() {
+            if clocks.len() == 2 || !clock.bytes().all(|b| b.is_ascii_digit()) {
+                break;
+            }
+            clocks.push(clock);
+            rest = rest[clock.len()..].trim_start();
+        }
+        fields.extend(clocks);
+
+        // Moves are resolved against the position, which needs kings and a legal setup.
+        let mut position = Position::from_fen_strict(fields.join(" "))?;
+
+        let mut operations = Vec::new();
+        for mut tokens in tokenize(rest)? {
+            let operands = tokens.split_off(1);
+            let operation = Operation::parse(tokens.remove(0), operands)?;
+
+            match operation {
+                Operation::HalfmoveClock(clock) => position.halfmove_clock = clock,
+                Operation::FullmoveNumber(number) => position.fullmove_number = number.max(1),
+                _ => {}
+            }
+            operations.push(operation);
+        }
+
+        Ok(Epd {
+            position,
+            operations,
+        })
+    }
+}
+
+fn write_string(f: &mut core::fmt::Formatter<'_>, s: &str) -> core::fmt::Result {
+    write!(f, " \"{s}\"")
+}
+
+fn write_operands(f: &mut core::fmt::Formatter<'_>, operands: &[String]) -> core::fmt::Result {
+    for operand in operands {
+        if operand.is_empty() || operand.contains(|c: char| c.is_whitespace() || c == ';') {
+            write_string(f, operand)?;
+        } else {
+            write!(f, " {operand}")?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.opcode())?;
+
+        match self {
+            Operation::BestMoves(moves)
+            | Operation::AvoidMoves(moves)
+            | Operation::PredictedVariation(moves) => write_operands(f, moves)?,
+            Operation::Id(s) | Operation::Comment(_, s) => write_string(f, s)?,
+            Operation::AnalysisDepth(depth) => write!(f, " {depth}")?,
+            Operation::CentipawnEvaluation(eval) => write!(f, " {eval}")?,
+            Operation::HalfmoveClock(clock) => write!(f, " {clock}")?,
+            Operation::FullmoveNumber(number) => write!(f, " {number}")?,
+            Operation::Perft { nodes, .. } => write!(f, " {nodes}")?,
+            Operation::Other { operands, .. } => write_operands(f, operands)?,
+        }
+
+        write!(f, ";")
+    }
+}
+
+impl Display for Epd {
+    /// Writes the first four FEN fields and the operations. The clocks of the position are only
+    /// written through `hmvc` and `fmvn` operations.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let fen = self.position.to_fen();
+        let fields = fen.split(' ').take(4).collect::<Vec<_>>();
+        write!(f, "{}", fields.join(" "))?;
+
+        for operation in &self.operations {
+            write!(f, " {operation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use crate::{
+        epd::{Epd, EpdError, Operation},
+        fen::{Fen, FenError, FenField},
+    };
+
+    #[test]
+    fn test_parse() {
+        let epd = Epd::from_str(
+            "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - \
+             bm O-O Nc3; am d4; id \"Italian; test 1\"; c0 \"a comment\"; acd 12; ce -35; \
+             pv O-O Nf6 d3; hmvc 4; fmvn 4; xyz \"quoted operand\" 7;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            epd.position.to_fen(),
+            "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4"
+        );
+        assert_eq!(
+            epd.best_moves(),
+            Some(&["O-O".to_string(), "Nc3".to_string()][..])
+        );
+        assert_eq!(epd.avoid_moves(), Some(&["d4".to_string()][..]));
+        assert_eq!(epd.id(), Some("Italian; test 1"));
+        assert_eq!(epd.comment(0), Some("a comment"));
+        assert_eq!(epd.comment(1), None);
+        assert_eq!(epd.predicted_variation().map(<[String]>::len), Some(3));
+        assert!(epd.operations.contains(&Operation::AnalysisDepth(12)));
+        assert!(epd
+            .operations
+            .contains(&Operation::CentipawnEvaluation(-35)));
+        assert_eq!(
+            epd.operations.last(),
+            Some(&Operation::Other {
+                opcode: "xyz".to_string(),
+                operands: vec!["quoted operand".to_string(), "7".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_perft_suite() {
+        let epd = Epd::from_str(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400 ;D3 8902",
+        )
+        .unwrap();
+
+        assert_eq!(epd.perft(1), Some(20));
+        assert_eq!(epd.perft(3), Some(8902));
+        assert_eq!(epd.perft(4), None);
+
+        let epd = Epd::from_str("8/8/8/8/8/8/8/k1K5 b - - 37 102 ;D1 3").unwrap();
+        assert_eq!(
+            (epd.position.halfmove_clock, epd.position.fullmove_number),
+            (37, 102)
+        );
+        assert_eq!(epd.perft(1), Some(3));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for record in [
+            "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - bm O-O Nc3; \
+             id \"Italian; test 1\"; c5 \"\"; ce 0; hmvc 4; fmvn 4;",
+            "8/8/8/8/8/8/8/k1K5 b - - D1 3; D2 26; xyz \"quoted operand\" 7;",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+        ] {
+            assert_eq!(Epd::from_str(record).unwrap().to_string(), record);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |record: &str| Epd::from_str(record).unwrap_err();
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -";
+
+        assert_eq!(
+            error("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w"),
+            EpdError::Fen(FenError::MissingField(FenField::Castling))
+        );
+        assert_eq!(
+            error(&format!("{start} id \"open;")),
+            EpdError::UnterminatedString
+        );
+        assert_eq!(
+            error(&format!("{start} 1x 2;")),
+            EpdError::InvalidOpcode("1x".to_string())
+        );
+        assert_eq!(
+            error(&format!("{start} acd deep;")),
+            EpdError::InvalidOperands {
+                opcode: "acd".to_string(),
+                operands: vec!["deep".to_string()],
+            }
+        );
+        assert!(matches!(
+            error(&format!("{start} bm;")),
+            EpdError::InvalidOperands { .. }
+        ));
+        assert!(matches!(
+            error(&format!("{start} id a b;")),
+            EpdError::InvalidOperands { .. }
+        ));
+        assert!(matches!(
+            error("8/8/8/8/8/8/8/8 w - - bm e4;"),
+            EpdError::Fen(FenError::InvalidPosition(_))
+        ));
+    }
+}
@@ -0,0 +1,33 @@
+use alloc::string::String;
+use core::fmt::Display;
+
+/// Error returned by the `FromStr` and `TryFrom` impls of the basic sdk types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidPiece(String),
+    InvalidPieceIndex(usize),
+    InvalidSquare(String),
+    InvalidColor(String),
+    InvalidCastling(String),
+    InvalidMaterialSignature(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::InvalidPiece(s) => write!(f, "Invalid piece: '{s}'"),
+            ParseError::InvalidPieceIndex(idx) => write!(f, "Invalid piece index: {idx}"),
+            ParseError::InvalidSquare(s) => write!(f, "Invalid square: '{s}', expected e.g. 'e4'"),
+            ParseError::InvalidColor(s) => write!(f, "Invalid color: '{s}', expected 'w' or 'b'"),
+            ParseError::InvalidCastling(s) => write!(f, "Invalid castling rights: '{s}'"),
+            ParseError::InvalidMaterialSignature(s) => {
+                write!(
+                    f,
+                    "Invalid material signature: '{s}', expected e.g. 'KRPvKR'"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
@@ -1,175 +1,462 @@
-use anyhow::anyhow;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Write};
 
 use crate::{
-    bitboard::Bitboard,
-    position::{Castling, Color, Piece, Position},
-    square::Square,
+    position::{Castling, CastlingKind, Color, Piece, Position},
+    square::{File, Rank, Square},
+    validation::InvalidPosition,
 };
 
 pub trait Fen {
-    fn from_fen(fen: String) -> anyhow::Result<Position>;
+    fn from_fen(fen: String) -> Result<Position, FenError>;
+    /// Writes the castling rights as in X-FEN: `KQkq` for the outermost rooks, which is plain
+    /// FEN for standard chess, and the rook file otherwise.
     fn to_fen(&self) -> String;
+    /// Like [`Fen::to_fen`], but writes every castling right as the rook file, as in
+    /// Shredder-FEN.
+    fn to_shredder_fen(&self) -> String;
+
+    /// Like [`Fen::from_fen`], but also rejects positions failing [`Position::validate`].
+    fn from_fen_strict(fen: String) -> Result<Position, FenError> {
+        let position = Self::from_fen(fen)?;
+        position.validate().map_err(FenError::InvalidPosition)?;
+
+        Ok(position)
+    }
 }
 
-impl Fen for Position {
-    #[allow(clippy::too_many_lines)]
-    fn from_fen(fen: String) -> anyhow::Result<Position> {
-        let mut position = Position {
-            pieces: [[Bitboard(0); 6]; 2],
-            occupied: Bitboard(0),
-            turn: Color::White,
-            castling: Castling::empty(),
-            en_passant: None,
-            halfmove_clock: 0,
-            fullmove_number: 1,
-        };
-        let mut fen = fen.split_whitespace();
-        let ranks = fen.next().unwrap().split('/');
-        let size = ranks.clone().count();
-        if size != 8 {
-            return Err(anyhow::anyhow!(
-                "Invalid FEN: Invalid number of ranks, got {}, expected 8",
-                size
-            ));
-        }
-
-        for (rank, rank_str) in ranks.enumerate() {
-            let mut file = 0;
-            for c in rank_str.chars() {
-                if let Some(digit) = c.to_digit(10) {
-                    file += digit as usize;
-                } else {
-                    let color = if c.is_uppercase() {
-                        Color::White
-                    } else {
-                        Color::Black
-                    };
-
-                    let piece = match c.to_ascii_lowercase() {
-                        'p' => Piece::Pawn,
-                        'n' => Piece::Knight,
-                        'b' => Piece::Bishop,
-                        'r' => Piece::Rook,
-                        'q' => Piece::Queen,
-                        'k' => Piece::King,
-                        _ => {
-                            return Err(anyhow::anyhow!(
-                                "Invalid FEN: Invalid piece character {}",
-                                c
-                            ))
-                        }
-                    };
-
-                    let square: Square =
-                        ((7 - u8::try_from(rank)?) * 8 + u8::try_from(file)?).try_into()?;
-                    file += 1;
-                    let idx = piece as usize;
-
-                    position.pieces[color as usize][idx] |= square.bitboard();
-                }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenField {
+    Placement,
+    Turn,
+    Castling,
+    EnPassant,
+    HalfmoveClock,
+    FullmoveNumber,
+}
+
+/// Error returned when parsing a FEN. Columns are 0-based character offsets into the FEN string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingField(FenField),
+    InvalidPlacement {
+        column: usize,
+        found: char,
+    },
+    InvalidRankLength {
+        rank: Rank,
+        column: usize,
+    },
+    InvalidRankCount {
+        count: usize,
+    },
+    InvalidField {
+        field: FenField,
+        column: usize,
+        value: String,
+    },
+    TrailingData {
+        column: usize,
+    },
+    InvalidPosition(InvalidPosition),
+}
+
+impl FenError {
+    #[must_use]
+    pub fn field(&self) -> Option<FenField> {
+        match self {
+            FenError::MissingField(field) | FenError::InvalidField { field, .. } => Some(*field),
+            FenError::InvalidPlacement { .. }
+            | FenError::InvalidRankLength { .. }
+            | FenError::InvalidRankCount { .. } => Some(FenField::Placement),
+            FenError::TrailingData { .. } | FenError::InvalidPosition(_) => None,
+        }
+    }
+
+    #[must_use]
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            FenError::InvalidPlacement { column, .. }
+            | FenError::InvalidRankLength { column, .. }
+            | FenError::InvalidField { column, .. }
+            | FenError::TrailingData { column } => Some(*column),
+            FenError::MissingField(_)
+            | FenError::InvalidRankCount { .. }
+            | FenError::InvalidPosition(_) => None,
+        }
+    }
+}
+
+impl Display for FenField {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FenField::Placement => write!(f, "piece placement"),
+            FenField::Turn => write!(f, "side to move"),
+            FenField::Castling => write!(f, "castling rights"),
+            FenField::EnPassant => write!(f, "en passant square"),
+            FenField::HalfmoveClock => write!(f, "halfmove clock"),
+            FenField::FullmoveNumber => write!(f, "fullmove number"),
+        }
+    }
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "Invalid FEN: Missing {field}"),
+            FenError::InvalidPlacement { column, found } => write!(
+                f,
+                "Invalid FEN: Invalid piece placement character '{found}' at column {column}"
+            ),
+            FenError::InvalidRankLength { rank, column } => write!(
+                f,
+                "Invalid FEN: Rank {rank} doesn't have 8 files, at column {column}"
+            ),
+            FenError::InvalidRankCount { count } => write!(
+                f,
+                "Invalid FEN: Invalid number of ranks, got {count}, expected 8"
+            ),
+            FenError::InvalidField {
+                field,
+                column,
+                value,
+            } => write!(
+                f,
+                "Invalid FEN: Invalid {field} '{value}' at column {column}"
+            ),
+            FenError::TrailingData { column } => {
+                write!(f, "Invalid FEN: Unexpected data at column {column}")
             }
+            FenError::InvalidPosition(invalid) => write!(f, "Invalid FEN: {invalid}"),
         }
+    }
+}
+
+impl core::error::Error for FenError {}
 
-        position.occupied = position.occupation(&Color::White) | position.occupation(&Color::Black);
+/// Splits on whitespace, returning each field with the column it starts at.
+fn split_fields(fen: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
 
-        let turn = fen.next().unwrap();
-        position.turn = match turn {
-            "w" => Color::White,
-            "b" => Color::Black,
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Invalid FEN: Invalid turn character {}",
-                    turn
-                ))
+    for (column, (idx, c)) in fen.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((column, idx)),
+            (true, Some((start_column, start_idx))) => {
+                fields.push((start_column, &fen[start_idx..idx]));
+                start = None;
             }
-        };
+            _ => {}
+        }
+    }
+
+    if let Some((start_column, start_idx)) = start {
+        fields.push((start_column, &fen[start_idx..]));
+    }
 
-        let castling: Castling = fen.next().unwrap().parse()?;
+    fields
+}
 
-        position.castling = castling;
+fn parse_placement(
+    position: &mut Position,
+    column: usize,
+    placement: &str,
+) -> Result<(), FenError> {
+    let count = placement.split('/').count();
+    if count != 8 {
+        return Err(FenError::InvalidRankCount { count });
+    }
 
-        let en_pass = fen.next().unwrap();
-        position.en_passant = if en_pass == "-" {
-            None
-        } else {
-            let mut chars = en_pass.chars();
-            let file_char = chars
-                .next()
-                .ok_or(anyhow!("Invalid FEN: Invalid en passant square: {en_pass}"))?;
-            let rank_char = chars
-                .next()
-                .ok_or(anyhow!("Invalid FEN: Invalid en passant square: {en_pass}"))?;
-            if (file_char as u8) < b'a' || (file_char as u8) > b'h' {
-                return Err(anyhow!("Invalid FEN: Invalid en passant file: {file_char}, expected a-h. En passant square: {en_pass}"));
+    let mut rank = Rank::R8;
+    let mut file = 0u8;
+    let mut previous_digit = false;
+
+    for (offset, c) in placement.chars().enumerate() {
+        let column = column + offset;
+
+        if c == '/' {
+            if file != 8 {
+                return Err(FenError::InvalidRankLength { rank, column });
             }
-            if (rank_char as u8) < b'1' || (rank_char as u8) > b'8' {
-                return Err(anyhow!("Invalid FEN: Invalid en passant rank: {rank_char}, expected 1-8. En passant square: {en_pass}"));
+            rank = (rank as u8 - 1)
+                .try_into()
+                .expect("BUG: Rank count checked above");
+            file = 0;
+            previous_digit = false;
+            continue;
+        }
+
+        if let Some(digit) = c.to_digit(10) {
+            if !(1..=8).contains(&digit) || previous_digit {
+                return Err(FenError::InvalidPlacement { column, found: c });
+            }
+            file += u8::try_from(digit).expect("BUG: Digit checked above");
+            if file > 8 {
+                return Err(FenError::InvalidRankLength { rank, column });
             }
-            let file = file_char as u8 - b'a';
-            let rank = rank_char as u8 - b'1';
+            previous_digit = true;
+            continue;
+        }
 
-            (rank * 8 + file).try_into().ok()
+        let color = if c.is_uppercase() {
+            Color::White
+        } else {
+            Color::Black
         };
 
+        let piece = match c.to_ascii_lowercase() {
+            'p' => Piece::Pawn,
+            'n' => Piece::Knight,
+            'b' => Piece::Bishop,
+            'r' => Piece::Rook,
+            'q' => Piece::Queen,
+            'k' => Piece::King,
+            _ => return Err(FenError::InvalidPlacement { column, found: c }),
+        };
+
+        if file >= 8 {
+            return Err(FenError::InvalidRankLength { rank, column });
+        }
+
+        let square: Square = (rank as u8 * 8 + file)
+            .try_into()
+            .expect("BUG: Square out of bounds");
+        position
+            .add_piece_at(square, piece, color)
+            .expect("BUG: Square visited twice");
+        file += 1;
+        previous_digit = false;
+    }
+
+    if file != 8 {
+        return Err(FenError::InvalidRankLength {
+            rank,
+            column: column + placement.chars().count(),
+        });
+    }
+
+    Ok(())
+}
+
+impl Fen for Position {
+    /// Parses a FEN. The halfmove clock and fullmove number may be omitted, as in EPD, and
+    /// default to `0` and `1`.
+    fn from_fen(fen: String) -> Result<Position, FenError> {
+        let mut position = Position::empty();
+
+        let mut fields = split_fields(&fen).into_iter();
+        let mut next = |field| fields.next().ok_or(FenError::MissingField(field));
+        let invalid = |field, (column, value): (usize, &str)| FenError::InvalidField {
+            field,
+            column,
+            value: value.to_string(),
+        };
+
+        let (column, placement) = next(FenField::Placement)?;
+        parse_placement(&mut position, column, placement)?;
+
+        let turn = next(FenField::Turn)?;
+        position.turn = turn.1.parse().map_err(|_| invalid(FenField::Turn, turn))?;
+
+        let castling = next(FenField::Castling)?;
+        position.castling =
+            parse_castling(&position, castling.1).ok_or(invalid(FenField::Castling, castling))?;
+
+        let en_passant = next(FenField::EnPassant)?;
+        position.en_passant = match en_passant.1 {
+            "-" => None,
+            square => Some(
+                square
+                    .parse()
+                    .map_err(|_| invalid(FenField::EnPassant, en_passant))?,
+            ),
+        };
+
+        if let Ok(halfmove_clock) = next(FenField::HalfmoveClock) {
+            position.halfmove_clock = halfmove_clock
+                .1
+                .parse()
+                .map_err(|_| invalid(FenField::HalfmoveClock, halfmove_clock))?;
+        }
+
+        if let Ok(fullmove_number) = next(FenField::FullmoveNumber) {
+            // Some tools write 0 for positions without history.
+            position.fullmove_number = fullmove_number
+                .1
+                .parse::<u16>()
+                .map_err(|_| invalid(FenField::FullmoveNumber, fullmove_number))?
+                .max(1);
+        }
+
+        if let Ok((column, _)) = next(FenField::FullmoveNumber) {
+            return Err(FenError::TrailingData { column });
+        }
+
+        position.refresh_hashes();
+
         Ok(position)
     }
 
     fn to_fen(&self) -> String {
-        let mut fen = String::new();
-        let mut empty = 0;
-        for rank in (0..8u8).rev() {
-            for file in 0..8u8 {
-                let square: Square = (rank * 8 + file)
-                    .try_into()
-                    .expect("BUG: Square out of bounds");
-                let piece = self.piece_at(&square);
-                if let Some((piece, color)) = piece {
-                    if empty != 0 {
-                        fen.push_str(&format!("{empty}"));
-                        empty = 0;
-                    }
-                    match color {
-                        Color::White => fen.push_str(&format!("{piece}").to_uppercase()),
-                        Color::Black => fen.push_str(&format!("{piece}").to_lowercase()),
-                    }
-                } else {
-                    empty += 1;
-                }
-            }
-            if empty != 0 {
-                fen.push_str(&format!("{empty}"));
-                empty = 0;
+        write_fen(self, false)
+    }
+
+    fn to_shredder_fen(&self) -> String {
+        write_fen(self, true)
+    }
+}
+
+/// Outermost rook on the back rank on the side of the king given by `kind`, which `K` and `Q`
+/// refer to in X-FEN.
+fn outermost_rook(position: &Position, kind: CastlingKind) -> Option<File> {
+    let color = kind.color();
+    let rooks = position.pieces[color as usize][Piece::Rook as usize];
+    let mut files = File::ALL.into_iter().filter(|&file| {
+        let square = Square::from((file, kind.back_rank()));
+        rooks.has(square) && position.castling_kind_for_rook(square, color) == Some(kind)
+    });
+
+    if kind.is_kingside() {
+        files.next_back()
+    } else {
+        files.next()
+    }
+}
+
+/// Parses castling rights given as `KQkq` (FEN and X-FEN) or rook files (Shredder-FEN and X-FEN),
+/// in any mix. Rights that don't match the board are kept as in standard chess, so that
+/// [`Position::validate`] can report them.
+fn parse_castling(position: &Position, castling: &str) -> Option<Castling> {
+    let mut rights = Castling::empty();
+    if castling == "-" {
+        return Some(rights);
+    }
+
+    for c in castling.chars() {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        let (kind, file) = match c.to_ascii_lowercase() {
+            c @ ('k' | 'q') => {
+                let kind = CastlingKind::new(color, c == 'k');
+                let file = outermost_rook(position, kind).unwrap_or(kind.standard_rook_file());
+                (kind, file)
             }
-            if rank != 0 {
-                fen.push('/');
+            c @ 'a'..='h' => {
+                let file = File::ALL[(c as u8 - b'a') as usize];
+                let square = Square::from((file, CastlingKind::new(color, true).back_rank()));
+                let kind = position
+                    .castling_kind_for_rook(square, color)
+                    .unwrap_or(CastlingKind::new(color, file > File::E));
+                (kind, file)
             }
+            _ => return None,
+        };
+
+        if rights.has_castling_kind(&kind) {
+            return None;
         }
+        rights.add_castling_rook(&kind, file);
+    }
 
-        fen.push(' ');
-        fen.push_str(&format!("{}", self.turn));
-        fen.push(' ');
-        fen.push_str(self.castling.to_string().as_str());
-        fen.push(' ');
-        if let Some(square) = &self.en_passant {
-            fen.push_str(&square.coords_str());
+    (!rights.is_empty()).then_some(rights)
+}
+
+fn write_castling(position: &Position, shredder: bool) -> String {
+    let mut castling = String::new();
+
+    for kind in CastlingKind::all() {
+        let Some(file) = position.castling.rook_file(&kind) else {
+            continue;
+        };
+
+        let symbol = if !shredder && outermost_rook(position, kind) == Some(file) {
+            if kind.is_kingside() {
+                'k'
+            } else {
+                'q'
+            }
         } else {
-            fen.push('-');
+            (b'a' + file as u8) as char
+        };
+
+        castling.push(match kind.color() {
+            Color::White => symbol.to_ascii_uppercase(),
+            Color::Black => symbol,
+        });
+    }
+
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    castling
+}
+
+fn write_fen(position: &Position, shredder: bool) -> String {
+    let mut fen = String::new();
+    let mut empty = 0;
+    for rank in (0..8u8).rev() {
+        for file in 0..8u8 {
+            let square: Square = (rank * 8 + file)
+                .try_into()
+                .expect("BUG: Square out of bounds");
+            let piece = position.piece_at(&square);
+            if let Some((piece, color)) = piece {
+                if empty != 0 {
+                    write!(fen, "{empty}").unwrap();
+                    empty = 0;
+                }
+                match color {
+                    Color::White => fen.push_str(&format!("{piece}").to_uppercase()),
+                    Color::Black => fen.push_str(&format!("{piece}").to_lowercase()),
+                }
+            } else {
+                empty += 1;
+            }
+        }
+        if empty != 0 {
+            write!(fen, "{empty}").unwrap();
+            empty = 0;
+        }
+        if rank != 0 {
+            fen.push('/');
         }
-        fen.push(' ');
-        fen.push_str(&format!("{}", self.halfmove_clock));
-        fen.push(' ');
-        fen.push_str(&format!("{}", self.fullmove_number));
+    }
 
-        fen
+    write!(fen, " {}", position.turn).unwrap();
+    write!(fen, " {}", write_castling(position, shredder)).unwrap();
+    fen.push(' ');
+    if let Some(square) = &position.en_passant {
+        fen.push_str(&square.coords_str());
+    } else {
+        fen.push('-');
     }
+    write!(
+        fen,
+        " {} {}",
+        position.halfmove_clock, position.fullmove_number
+    )
+    .unwrap();
+
+    fen
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::fen::Fen;
-    use crate::position::Position;
+    use crate::fen::{Fen, FenError, FenField};
+    use crate::position::{CastlingKind, Position};
+    use crate::square::{File, Rank};
 
+    #[test]
     fn test_starting_fen() {
         let starting_pos = Position::default();
 
@@ -178,4 +465,146 @@ mod tests {
             "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
         );
     }
+
+    #[test]
+    fn test_round_trip() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3",
+            "8/8/8/8/8/8/8/k1K5 w - - 37 102",
+        ] {
+            assert_eq!(Position::from_fen(fen.to_string()).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let pos = Position::from_fen(fen.to_string()).unwrap();
+        assert_eq!(
+            pos.castling.rook_file(&CastlingKind::WhiteQueenside),
+            Some(File::F)
+        );
+        assert_eq!(pos.to_shredder_fen(), fen);
+        assert_eq!(
+            pos.to_fen(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+        assert_eq!(Position::from_fen(pos.to_fen()).unwrap(), pos);
+
+        // X-FEN names the rook file when the right isn't for the outermost rook.
+        let pos = Position::from_fen("4k3/8/8/8/8/8/8/1K2R2R w E - 0 1".to_string()).unwrap();
+        assert_eq!(
+            pos.castling.rook_file(&CastlingKind::WhiteKingside),
+            Some(File::E)
+        );
+        assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/1K2R2R w E - 0 1");
+        let pos = Position::from_fen("4k3/8/8/8/8/8/8/1K2R2R w K - 0 1".to_string()).unwrap();
+        assert_eq!(
+            pos.castling.rook_file(&CastlingKind::WhiteKingside),
+            Some(File::H)
+        );
+
+        assert_eq!(
+            Position::from_fen(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1".to_string()
+            )
+            .unwrap(),
+            Position::default()
+        );
+        assert!(Position::from_fen("4k3/8/8/8/8/8/8/1K2R2R w EH - 0 1".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_optional_clocks() {
+        let pos =
+            Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -".to_string())
+                .unwrap();
+        assert_eq!((pos.halfmove_clock, pos.fullmove_number), (0, 1));
+
+        let pos = Position::from_fen(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 7".to_string(),
+        )
+        .unwrap();
+        assert_eq!((pos.halfmove_clock, pos.fullmove_number), (7, 1));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |fen: &str| Position::from_fen(fen.to_string()).unwrap_err();
+
+        assert_eq!(error(""), FenError::MissingField(FenField::Placement));
+        assert_eq!(
+            error("8/8/8/8/8/8/8/8"),
+            FenError::MissingField(FenField::Turn)
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8 w - - 0 1"),
+            FenError::InvalidRankCount { count: 7 }
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/8/8 w - - 0 1"),
+            FenError::InvalidRankCount { count: 9 }
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/ppppppppp w - - 0 1"),
+            FenError::InvalidRankLength {
+                rank: Rank::R1,
+                column: 22
+            }
+        );
+        assert_eq!(
+            error("8/7/8/8/8/8/8/8 w - - 0 1"),
+            FenError::InvalidRankLength {
+                rank: Rank::R7,
+                column: 3
+            }
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/7 w - - 0 1"),
+            FenError::InvalidRankLength {
+                rank: Rank::R1,
+                column: 15
+            }
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/44 w - - 0 1"),
+            FenError::InvalidPlacement {
+                column: 15,
+                found: '4'
+            }
+        );
+        assert_eq!(
+            error("8/8/8/3x4/8/8/8/8 w - - 0 1"),
+            FenError::InvalidPlacement {
+                column: 7,
+                found: 'x'
+            }
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/8 x - - 0 1"),
+            FenError::InvalidField {
+                field: FenField::Turn,
+                column: 16,
+                value: "x".to_string()
+            }
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/8 w KK - 0 1").field(),
+            Some(FenField::Castling)
+        );
+        assert_eq!(error("8/8/8/8/8/8/8/8 w - e9 0 1").column(), Some(20));
+        assert_eq!(
+            error("8/8/8/8/8/8/8/8 w - - x 1").field(),
+            Some(FenField::HalfmoveClock)
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/8 w - - 0 -1").field(),
+            Some(FenField::FullmoveNumber)
+        );
+        assert_eq!(
+            error("8/8/8/8/8/8/8/8 w - - 0 1 extra"),
+            FenError::TrailingData { column: 26 }
+        );
+    }
 }
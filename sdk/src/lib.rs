@@ -1,10 +1,27 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(clippy::pedantic)]
 #![allow(clippy::missing_panics_doc, clippy::missing_errors_doc)]
 
+extern crate alloc;
 extern crate log;
 
 pub mod bitboard;
+pub mod builder;
+pub mod chess960;
+pub mod codec;
+pub mod epd;
+pub mod error;
 pub mod fen;
 pub mod lookup;
+pub mod material;
+pub mod pawn_structure;
 pub mod position;
+pub mod random;
+#[cfg(feature = "serde")]
+pub mod serialization;
 pub mod square;
+#[cfg(feature = "std")]
+pub mod svg;
+pub mod symmetry;
+pub mod validation;
+pub mod zobrist;
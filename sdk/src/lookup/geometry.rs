@@ -0,0 +1,185 @@
+use crate::{
+    bitboard::{Bitboard, Direction},
+    square::Square,
+};
+
+/// Chebyshev distance, i.e. the number of king moves between two squares.
+pub static DISTANCE: [[u8; 64]; 64] = gen_distances();
+/// Manhattan distance, i.e. the number of rook moves of length one between two squares.
+pub static MANHATTAN_DISTANCE: [[u8; 64]; 64] = gen_manhattan_distances();
+/// The whole rank, file or diagonal through both squares, empty if they are not aligned.
+pub static LINE: [[Bitboard; 64]; 64] = gen_lines();
+/// Squares from a square (exclusive) to the edge of the board, indexed by `[direction][square]`.
+pub static RAYS: [[Bitboard; 64]; 8] = gen_rays();
+
+#[must_use]
+pub const fn gen_distances() -> [[u8; 64]; 64] {
+    let mut result = [[0; 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut to = 0;
+        while to < 64 {
+            let (rank_diff, file_diff) = coordinate_diffs(Square::ALL[from], Square::ALL[to]);
+            result[from][to] = if rank_diff > file_diff {
+                rank_diff
+            } else {
+                file_diff
+            };
+            to += 1;
+        }
+        from += 1;
+    }
+    result
+}
+
+#[must_use]
+pub const fn gen_manhattan_distances() -> [[u8; 64]; 64] {
+    let mut result = [[0; 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut to = 0;
+        while to < 64 {
+            let (rank_diff, file_diff) = coordinate_diffs(Square::ALL[from], Square::ALL[to]);
+            result[from][to] = rank_diff + file_diff;
+            to += 1;
+        }
+        from += 1;
+    }
+    result
+}
+
+#[must_use]
+pub const fn gen_rays() -> [[Bitboard; 64]; 8] {
+    let directions = Direction::all();
+    let mut rays = [[Bitboard(0); 64]; 8];
+    let mut dir = 0;
+    while dir < 8 {
+        let direction = directions[dir];
+        let mut sq = 0;
+        while sq < 64 {
+            let mut ray = 0;
+            let mut current = Square::ALL[sq].bitboard().shift(&direction);
+            while !current.is_empty() {
+                ray |= current.0;
+                current = current.shift(&direction);
+            }
+            rays[direction as usize][sq] = Bitboard(ray);
+            sq += 1;
+        }
+        dir += 1;
+    }
+    rays
+}
+
+#[must_use]
+#[allow(clippy::large_stack_arrays)]
+pub const fn gen_lines() -> [[Bitboard; 64]; 64] {
+    let rays = gen_rays();
+    let mut lines = [[Bitboard(0); 64]; 64];
+    let mut from = 0;
+    while from < 64 {
+        let mut to = 0;
+        while to < 64 {
+            let from_sq = Square::ALL[from];
+            if let Some(direction) = from_sq.direction_to(Square::ALL[to]) {
+                lines[from][to] = Bitboard(
+                    rays[direction as usize][from].0
+                        | rays[direction.opposite() as usize][from].0
+                        | from_sq.bitboard().0,
+                );
+            }
+            to += 1;
+        }
+        from += 1;
+    }
+    lines
+}
+
+const fn coordinate_diffs(from: Square, to: Square) -> (u8, u8) {
+    let rank_diff = (from.rank() as u8).abs_diff(to.rank() as u8);
+    let file_diff = (from.file() as u8).abs_diff(to.file() as u8);
+    (rank_diff, file_diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::{Bitboard, Direction},
+        lookup::geometry::{DISTANCE, LINE, MANHATTAN_DISTANCE, RAYS},
+        square::{File, Rank, Square},
+    };
+
+    #[test]
+    fn test_distances() {
+        assert_eq!(DISTANCE[Square::A1 as usize][Square::H8 as usize], 7);
+        assert_eq!(DISTANCE[Square::E4 as usize][Square::F6 as usize], 2);
+        assert_eq!(DISTANCE[Square::E4 as usize][Square::E4 as usize], 0);
+        assert_eq!(
+            MANHATTAN_DISTANCE[Square::A1 as usize][Square::H8 as usize],
+            14
+        );
+        assert_eq!(
+            MANHATTAN_DISTANCE[Square::E4 as usize][Square::F6 as usize],
+            3
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(
+            LINE[Square::A1 as usize][Square::A5 as usize],
+            File::A.bitboard()
+        );
+        assert_eq!(
+            LINE[Square::C3 as usize][Square::F3 as usize],
+            Rank::R3.bitboard()
+        );
+        assert_eq!(
+            LINE[Square::H8 as usize][Square::B2 as usize],
+            Bitboard(0x8040_2010_0804_0201)
+        );
+        assert_eq!(LINE[Square::A1 as usize][Square::B3 as usize], Bitboard(0));
+        assert_eq!(LINE[Square::E4 as usize][Square::E4 as usize], Bitboard(0));
+    }
+
+    #[test]
+    fn test_rays() {
+        let ray = RAYS[Direction::NorthWest as usize][Square::E4 as usize];
+        assert_eq!(
+            ray,
+            Square::D5.bitboard()
+                | Square::C6.bitboard()
+                | Square::B7.bitboard()
+                | Square::A8.bitboard()
+        );
+        assert!(RAYS[Direction::East as usize][Square::H4 as usize].is_empty());
+        assert_eq!(
+            RAYS[Direction::South as usize][Square::A8 as usize],
+            File::A.bitboard() ^ Square::A8.bitboard()
+        );
+    }
+
+    #[test]
+    fn test_direction_to() {
+        assert_eq!(Square::E4.direction_to(Square::E8), Some(Direction::North));
+        assert_eq!(
+            Square::E4.direction_to(Square::B1),
+            Some(Direction::SouthWest)
+        );
+        assert_eq!(
+            Square::E4.direction_to(Square::H7),
+            Some(Direction::NorthEast)
+        );
+        assert_eq!(Square::E4.direction_to(Square::A4), Some(Direction::West));
+        assert_eq!(Square::E4.direction_to(Square::F6), None);
+        assert_eq!(Square::E4.direction_to(Square::E4), None);
+
+        for from in Square::iter() {
+            for direction in Direction::all() {
+                for to in RAYS[direction as usize][from as usize] {
+                    assert_eq!(from.direction_to(to), Some(direction));
+                }
+            }
+        }
+    }
+}
@@ -1,22 +1,29 @@
 use crate::bitboard::Bitboard;
 
+pub static IN_BETWEEN: [[Bitboard; 64]; 64] = generate_in_between_squares();
+
 #[must_use]
-pub fn generate_in_between_squares() -> [[Bitboard; 64]; 64] {
+#[allow(clippy::large_stack_arrays)]
+pub const fn generate_in_between_squares() -> [[Bitboard; 64]; 64] {
     let mut result = [[Bitboard(0); 64]; 64];
 
-    for sq1 in 0..64u8 {
-        for sq2 in 0..64u8 {
-            result[sq1 as usize][sq2 as usize] = between(sq1, sq2).into();
+    let mut sq1 = 0;
+    while sq1 < 64 {
+        let mut sq2 = 0;
+        while sq2 < 64 {
+            result[sq1 as usize][sq2 as usize] = Bitboard(between(sq1, sq2));
+            sq2 += 1;
         }
+        sq1 += 1;
     }
 
     result
 }
 
 #[allow(arithmetic_overflow)]
-fn between(sq1: u8, sq2: u8) -> u64 {
-    let sq1 = u64::from(sq1);
-    let sq2 = u64::from(sq2);
+const fn between(sq1: u8, sq2: u8) -> u64 {
+    let sq1 = sq1 as u64;
+    let sq2 = sq2 as u64;
 
     let m1 = u64::MAX;
     let a2a7: u64 = 0x0001_0101_0101_0100;
@@ -32,7 +39,6 @@ fn between(sq1: u8, sq2: u8) -> u64 {
     line += 2 * (((rank & 7).wrapping_sub(1)) >> 58);
     line += (((rank.wrapping_sub(file)) & 15).wrapping_sub(1)) & b2g7;
     line += (((rank.wrapping_add(file)) & 15).wrapping_sub(1)) & h1b7;
-    let mut line = Into::<u64>::into(line);
     line = line.wrapping_mul(btwn & btwn.wrapping_neg());
 
     line & btwn 
@@ -1,6 +1,6 @@
 use crate::{
     bitboard::Bitboard,
-    square::{File, Rank},
+    square::{File, Rank, Square},
 };
 
 const FILE_A: Bitboard = File::A.bitboard();
@@ -8,22 +8,26 @@ const FILE_H: Bitboard = File::H.bitboard();
 const RANK_1: Bitboard = Rank::R1.bitboard();
 const RANK_8: Bitboard = Rank::R8.bitboard();
 
+pub static KING_ATTACKS: [Bitboard; 64] = gen_king_attacks();
+
 #[must_use]
-pub fn gen_king_attacks() -> [Bitboard; 64] {
+pub const fn gen_king_attacks() -> [Bitboard; 64] {
     let mut king_attacks = [Bitboard(0); 64];
-    for sq in crate::square::Square::iter() {
-        king_attacks[sq as usize] = mask_king_attacks(sq.bitboard());
+    let mut idx = 0;
+    while idx < 64 {
+        king_attacks[idx] = mask_king_attacks(Square::ALL[idx].bitboard());
+        idx += 1;
     }
     king_attacks
 }
 
 #[must_use]
-pub fn mask_king_attacks(king: Bitboard) -> Bitboard {
-    let mut attacks = king;
-    attacks |= (attacks << 1) & !FILE_A;
-    attacks |= (attacks >> 1) & !FILE_H;
-    attacks |= (attacks << 8) & !RANK_1;
-    attacks |= (attacks >> 8) & !RANK_8;
+pub const fn mask_king_attacks(king: Bitboard) -> Bitboard {
+    let mut attacks = king.0;
+    attacks |= (attacks << 1) & !FILE_A.0;
+    attacks |= (attacks >> 1) & !FILE_H.0;
+    attacks |= (attacks << 8) & !RANK_1.0;
+    attacks |= (attacks >> 8) & !RANK_8.0;
 
-    attacks ^ king
+    Bitboard(attacks ^ king.0)
 }
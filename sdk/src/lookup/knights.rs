@@ -10,27 +10,32 @@ const FILE_H: Bitboard = File::H.bitboard();
 const FILE_AB: Bitboard = Bitboard(FILE_A.0 | FILE_B.0);
 const FILE_GH: Bitboard = Bitboard(FILE_G.0 | FILE_H.0);
 
+pub static KNIGHT_ATTACKS: [Bitboard; 64] = gen_knight_attacks();
+
 #[must_use]
-pub fn gen_knight_attacks() -> [Bitboard; 64] {
+pub const fn gen_knight_attacks() -> [Bitboard; 64] {
     let mut knight_attacks = [Bitboard(0); 64];
-    for sq in Square::iter() {
-        knight_attacks[sq as usize] = mask_knights_attacks(sq.into());
+    let mut idx = 0;
+    while idx < 64 {
+        knight_attacks[idx] = mask_knights_attacks(Square::ALL[idx].bitboard());
+        idx += 1;
     }
     knight_attacks
 }
 
 #[must_use]
-pub fn mask_knights_attacks(bb: Bitboard) -> Bitboard {
-    let mut knight_bb: Bitboard = Bitboard(0);
+pub const fn mask_knights_attacks(bb: Bitboard) -> Bitboard {
+    let bb = bb.0;
+    let mut knight_bb = 0;
 
-    knight_bb |= (bb << 17) & !FILE_A;
-    knight_bb |= (bb << 15) & !FILE_H;
-    knight_bb |= (bb << 10) & !FILE_AB;
-    knight_bb |= (bb << 6) & !FILE_GH;
-    knight_bb |= (bb >> 17) & !FILE_H;
-    knight_bb |= (bb >> 15) & !FILE_A;
-    knight_bb |= (bb >> 10) & !FILE_GH;
-    knight_bb |= (bb >> 6) & !FILE_AB;
+    knight_bb |= (bb << 17) & !FILE_A.0;
+    knight_bb |= (bb << 15) & !FILE_H.0;
+    knight_bb |= (bb << 10) & !FILE_AB.0;
+    knight_bb |= (bb << 6) & !FILE_GH.0;
+    knight_bb |= (bb >> 17) & !FILE_H.0;
+    knight_bb |= (bb >> 15) & !FILE_A.0;
+    knight_bb |= (bb >> 10) & !FILE_GH.0;
+    knight_bb |= (bb >> 6) & !FILE_AB.0;
 
-    knight_bb 
+    Bitboard(knight_bb)
 }
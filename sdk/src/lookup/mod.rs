@@ -2,4 +2,5 @@ pub mod king;
 pub mod knights;
 pub mod pawns;
 pub mod sliders;
+pub mod geometry;
 pub mod in_between;
@@ -5,70 +5,80 @@ use crate::position::Color;
 use crate::square::Rank;
 use crate::square::Square;
 
+pub static PAWN_SINGLE_MOVES: [[Bitboard; 64]; 2] = gen_single_pawn_moves();
+pub static PAWN_DOUBLE_MOVES: [[Bitboard; 64]; 2] = gen_double_pawn_moves();
+pub static PAWN_ATTACKS: [[Bitboard; 64]; 2] = gen_pawn_attacks();
+
+const COLORS: [Color; 2] = [Color::White, Color::Black];
+
+const fn forward(color: Color) -> Direction {
+    match color {
+        Color::White => Direction::North,
+        Color::Black => Direction::South,
+    }
+}
+
 #[must_use]
-pub fn gen_single_pawn_moves() -> [[Bitboard; 64]; 2] {
+pub const fn gen_single_pawn_moves() -> [[Bitboard; 64]; 2] {
     let mut pawn_moves = [[EMPTY; 64]; 2];
-    for color in Color::iter() {
-        let direction = match color {
-            Color::White => Direction::North,
-            Color::Black => Direction::South,
-        };
+    let mut color = 0;
+    while color < 2 {
+        let direction = forward(COLORS[color]);
 
-        for sq in Square::iter() {
-            pawn_moves[color as usize][sq as usize] |= sq.bitboard().shift(&direction);
+        let mut sq = 0;
+        while sq < 64 {
+            pawn_moves[color][sq] = Square::ALL[sq].bitboard().shift(&direction);
+            sq += 1;
         }
+        color += 1;
     }
     pawn_moves
 }
 
 #[must_use]
-pub fn gen_double_pawn_moves() -> [[Bitboard; 64]; 2] {
+pub const fn gen_double_pawn_moves() -> [[Bitboard; 64]; 2] {
     let mut pawn_moves = [[EMPTY; 64]; 2];
-    for color in Color::iter() {
-        let direction = match color {
-            Color::White => Direction::North,
-            Color::Black => Direction::South,
-        };
+    let mut color = 0;
+    while color < 2 {
+        let direction = forward(COLORS[color]);
 
-        for sq in Square::iter() {
-            let rank = sq.rank();
-            if rank == Rank::R2 || rank == Rank::R7 {
-                pawn_moves[color as usize][sq as usize] |=
-                    sq.bitboard().shift(&direction).shift(&direction);
+        let mut sq = 0;
+        while sq < 64 {
+            let square = Square::ALL[sq];
+            let rank = square.rank();
+            if matches!(rank, Rank::R2 | Rank::R7) {
+                pawn_moves[color][sq] = square.bitboard().shift(&direction).shift(&direction);
             }
+            sq += 1;
         }
+        color += 1;
     }
     pawn_moves
 }
 
 #[must_use]
-pub fn gen_pawn_attacks() -> [[Bitboard; 64]; 2] {
+pub const fn gen_pawn_attacks() -> [[Bitboard; 64]; 2] {
     let mut pawn_attacks = [[EMPTY; 64]; 2];
-    for color in Color::iter() {
-        let (first_dir, second_dir) = match color {
-            Color::White => (Direction::NorthEast, Direction::NorthWest),
-            Color::Black => (Direction::SouthEast, Direction::SouthWest),
-        };
-
-        for sq in Square::iter() {
-            pawn_attacks[color as usize][sq as usize] |= sq.bitboard().shift(&first_dir);
-            pawn_attacks[color as usize][sq as usize] |= sq.bitboard().shift(&second_dir);
+    let mut color = 0;
+    while color < 2 {
+        let mut sq = 0;
+        while sq < 64 {
+            pawn_attacks[color][sq] =
+                mask_pawns_attacks(Square::ALL[sq].bitboard(), &COLORS[color]);
+            sq += 1;
         }
+        color += 1;
     }
 
     pawn_attacks
 }
 
 #[must_use]
-pub fn mask_pawns_attacks(bb: Bitboard, color: &Color) -> Bitboard {
+pub const fn mask_pawns_attacks(bb: Bitboard, color: &Color) -> Bitboard {
     let (first_dir, second_dir) = match color {
         Color::White => (Direction::NorthEast, Direction::NorthWest),
         Color::Black => (Direction::SouthEast, Direction::SouthWest),
     };
 
-    let mut attacks = EMPTY;
-    attacks |= bb.shift(&first_dir);
-    attacks |= bb.shift(&second_dir);
-
-    attacks
+    Bitboard(bb.shift(&first_dir).0 | bb.shift(&second_dir).0)
 }
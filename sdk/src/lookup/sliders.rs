@@ -1,3 +1,5 @@
+use core::fmt::Display;
+
 use crate::{bitboard::Bitboard, square::Square, position::Piece};
 
 const BISHOP_OFFSETS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
@@ -20,12 +22,12 @@ impl From<Slider> for Piece {
     }
 }
 
-impl ToString for Slider {
-    fn to_string(&self) -> String {
+impl Display for Slider {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            Slider::Bishop => "Bishop".to_string(),
-            Slider::Rook => "Rook".to_string(),
-            Slider::Queen => "Queen".to_string(),
+            Slider::Bishop => write!(f, "Bishop"),
+            Slider::Rook => write!(f, "Rook"),
+            Slider::Queen => write!(f, "Queen"),
         }
     }
 }
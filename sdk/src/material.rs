@@ -0,0 +1,279 @@
+use alloc::string::ToString;
+use core::{fmt::Display, str::FromStr};
+
+use crate::{
+    bitboard::Bitboard,
+    error::ParseError,
+    position::{Color, Piece, Position},
+    zobrist::PIECE_KEYS,
+};
+
+const LIGHT_SQUARES: Bitboard = Bitboard(0x55AA_55AA_55AA_55AA);
+
+/// Order in which pieces appear in a signature.
+const SIGNATURE_ORDER: [Piece; 6] = [
+    Piece::King,
+    Piece::Queen,
+    Piece::Rook,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Pawn,
+];
+
+/// Piece counts of both sides, written as e.g. `KRPvKR` with white first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialSignature {
+    counts: [[u8; 6]; 2],
+}
+
+/// Material balance in pawn units, using the usual 1/3/3/5/9 piece values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialBalance {
+    /// Both sides have the same pieces.
+    Equal,
+    /// Both sides have the same value in different pieces, e.g. a rook against bishop and two
+    /// pawns.
+    Imbalanced,
+    Advantage {
+        color: Color,
+        pawns: u16,
+    },
+}
+
+impl MaterialSignature {
+    #[must_use]
+    pub fn count(&self, color: Color, piece: Piece) -> u8 {
+        self.counts[color as usize][piece as usize]
+    }
+
+    /// Zobrist key of the piece counts, equal to `Position::material_hash` of positions with
+    /// this signature.
+    #[must_use]
+    pub fn key(&self) -> u64 {
+        let mut key = 0;
+        for color in Color::iter() {
+            for piece in Piece::all() {
+                for idx in 0..self.count(color, piece) {
+                    key ^= PIECE_KEYS[color as usize][piece as usize][idx as usize];
+                }
+            }
+        }
+
+        key
+    }
+
+    #[must_use]
+    pub fn color_flipped(&self) -> MaterialSignature {
+        MaterialSignature {
+            counts: [self.counts[1], self.counts[0]],
+        }
+    }
+
+    /// Value of the side's pieces in pawn units, kings excluded.
+    #[must_use]
+    pub fn value(&self, color: Color) -> u16 {
+        Piece::all()
+            .into_iter()
+            .map(|piece| u16::from(self.count(color, piece)) * piece_value(piece))
+            .sum()
+    }
+
+    #[must_use]
+    pub fn balance(&self) -> MaterialBalance {
+        let white = self.value(Color::White);
+        let black = self.value(Color::Black);
+
+        if self.counts[0] == self.counts[1] {
+            MaterialBalance::Equal
+        } else if white == black {
+            MaterialBalance::Imbalanced
+        } else if white > black {
+            MaterialBalance::Advantage {
+                color: Color::White,
+                pawns: white - black,
+            }
+        } else {
+            MaterialBalance::Advantage {
+                color: Color::Black,
+                pawns: black - white,
+            }
+        }
+    }
+}
+
+fn piece_value(piece: Piece) -> u16 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight | Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 0,
+    }
+}
+
+impl Display for MaterialSignature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (idx, color) in Color::iter().enumerate() {
+            if idx != 0 {
+                write!(f, "v")?;
+            }
+            for piece in SIGNATURE_ORDER {
+                for _ in 0..self.count(color, piece) {
+                    write!(f, "{}", piece.to_string().to_uppercase())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for MaterialSignature {
+    type Err = ParseError;
+
+    /// Parses signatures such as `"KRPvKR"`. Letters of a side may come in any order.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseError::InvalidMaterialSignature(s.to_string());
+
+        let (white, black) = s.split_once('v').ok_or_else(error)?;
+        let mut counts = [[0u8; 6]; 2];
+        for (side, pieces) in [white, black].into_iter().enumerate() {
+            for letter in pieces.chars() {
+                if !letter.is_ascii_uppercase() {
+                    return Err(error());
+                }
+
+                let piece = Piece::from_str(&letter.to_string()).map_err(|_| error())?;
+                let count = &mut counts[side][piece as usize];
+                *count = count.checked_add(1).ok_or_else(error)?;
+            }
+
+            if counts[side][Piece::King as usize] != 1 {
+                return Err(error());
+            }
+        }
+
+        Ok(MaterialSignature { counts })
+    }
+}
+
+impl Position {
+    #[must_use]
+    pub fn material_signature(&self) -> MaterialSignature {
+        let mut counts = [[0; 6]; 2];
+        for color in Color::iter() {
+            for piece in Piece::all() {
+                counts[color as usize][piece as usize] =
+                    self.pieces[color as usize][piece as usize].count();
+            }
+        }
+
+        MaterialSignature { counts }
+    }
+
+    /// Returns true if neither side can checkmate by any sequence of legal moves, for the common
+    /// cases: king against king with at most a single minor piece, and any number of bishops all
+    /// standing on squares of the same color.
+    #[must_use]
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces = |piece: Piece| {
+            self.pieces[Color::White as usize][piece as usize]
+                | self.pieces[Color::Black as usize][piece as usize]
+        };
+
+        if !(pieces(Piece::Pawn) | pieces(Piece::Rook) | pieces(Piece::Queen)).is_empty() {
+            return false;
+        }
+
+        let knights = pieces(Piece::Knight);
+        let bishops = pieces(Piece::Bishop);
+
+        match knights.count() {
+            0 => (bishops & LIGHT_SQUARES).is_empty() || (bishops & !LIGHT_SQUARES).is_empty(),
+            1 => bishops.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use crate::{
+        fen::Fen,
+        material::{MaterialBalance, MaterialSignature},
+        position::{Color, Position},
+    };
+
+    fn position(fen: &str) -> Position {
+        Position::from_fen(fen.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_signature() {
+        let pos = position("8/8/4k3/3r4/8/2RP4/4K3/8 w - - 0 1");
+        let signature = pos.material_signature();
+
+        assert_eq!(signature.to_string(), "KRPvKR");
+        assert_eq!(MaterialSignature::from_str("KRPvKR"), Ok(signature));
+        assert_eq!(MaterialSignature::from_str("KPRvKR"), Ok(signature));
+        assert_eq!(signature.key(), pos.material_hash);
+        assert_eq!(signature.color_flipped().to_string(), "KRvKRP");
+        assert_eq!(
+            Position::default().material_signature().to_string(),
+            "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP"
+        );
+
+        assert!(MaterialSignature::from_str("KRP").is_err());
+        assert!(MaterialSignature::from_str("RvK").is_err());
+        assert!(MaterialSignature::from_str("KxvK").is_err());
+        assert!(MaterialSignature::from_str("KrvK").is_err());
+    }
+
+    #[test]
+    fn test_balance() {
+        let balance = |s: &str| MaterialSignature::from_str(s).unwrap().balance();
+
+        assert_eq!(balance("KRPvKRP"), MaterialBalance::Equal);
+        assert_eq!(balance("KRvKBPP"), MaterialBalance::Imbalanced);
+        assert_eq!(
+            balance("KRPvKR"),
+            MaterialBalance::Advantage {
+                color: Color::White,
+                pawns: 1
+            }
+        );
+        assert_eq!(
+            balance("KvKQ"),
+            MaterialBalance::Advantage {
+                color: Color::Black,
+                pawns: 9
+            }
+        );
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        for fen in [
+            "8/8/4k3/8/8/8/4K3/8 w - - 0 1",
+            "8/8/4k3/8/8/2B5/4K3/8 w - - 0 1",
+            "8/8/4k3/8/8/2n5/4K3/8 w - - 0 1",
+            "8/8/4k3/2b5/8/2B5/4K3/8 w - - 0 1",
+            "8/8/4k3/8/8/2B1B3/4K3/8 w - - 0 1",
+        ] {
+            assert!(position(fen).is_insufficient_material(), "{fen}");
+        }
+
+        for fen in [
+            "8/8/4k3/1b6/8/2B5/4K3/8 w - - 0 1",
+            "8/8/4k3/8/8/2BN4/4K3/8 w - - 0 1",
+            "8/8/4k3/8/8/2NN4/4K3/8 w - - 0 1",
+            "8/8/4k3/8/8/2n5/4KB2/8 w - - 0 1",
+            "8/8/4k3/8/8/2P5/4K3/8 w - - 0 1",
+            "8/8/4k3/8/8/2R5/4K3/8 w - - 0 1",
+        ] {
+            assert!(!position(fen).is_insufficient_material(), "{fen}");
+        }
+    }
+}
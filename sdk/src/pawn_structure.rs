@@ -0,0 +1,293 @@
+//! Set-wise pawn structure terms. Every function works on whole sets of pawns at once, the
+//! results are bitboards of pawns, squares or whole files.
+
+use crate::{
+    bitboard::{Bitboard, Direction},
+    lookup::pawns::mask_pawns_attacks,
+    position::{Color, Piece, Position},
+    square::Rank,
+};
+
+/// Pawn structure terms of both sides, indexed by color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawnStructure {
+    pub passed: [Bitboard; 2],
+    pub isolated: [Bitboard; 2],
+    /// Pawns sharing their file with another pawn of the same color, all of them included.
+    pub doubled: [Bitboard; 2],
+    pub backward: [Bitboard; 2],
+    /// Pawns defended by another pawn or standing next to one.
+    pub connected: [Bitboard; 2],
+    pub candidate: [Bitboard; 2],
+    pub front_spans: [Bitboard; 2],
+    pub rear_spans: [Bitboard; 2],
+    pub attack_spans: [Bitboard; 2],
+    /// Files without pawns of the given color, but with enemy pawns.
+    pub half_open_files: [Bitboard; 2],
+    /// Files without any pawns.
+    pub open_files: Bitboard,
+    pub outposts: [Bitboard; 2],
+}
+
+impl PawnStructure {
+    #[must_use]
+    pub fn new(white_pawns: Bitboard, black_pawns: Bitboard) -> Self {
+        let pawns = [white_pawns, black_pawns];
+        let own = |f: fn(Bitboard, Color) -> Bitboard| {
+            [f(white_pawns, Color::White), f(black_pawns, Color::Black)]
+        };
+        let per_color = |f: fn(Bitboard, Bitboard, Color) -> Bitboard| {
+            [
+                f(white_pawns, black_pawns, Color::White),
+                f(black_pawns, white_pawns, Color::Black),
+            ]
+        };
+
+        PawnStructure {
+            passed: per_color(passed),
+            isolated: pawns.map(isolated),
+            doubled: pawns.map(doubled),
+            backward: per_color(backward),
+            connected: own(connected),
+            candidate: per_color(candidate),
+            front_spans: own(front_span),
+            rear_spans: own(rear_span),
+            attack_spans: own(attack_span),
+            half_open_files: [
+                half_open_files(white_pawns, black_pawns),
+                half_open_files(black_pawns, white_pawns),
+            ],
+            open_files: open_files(white_pawns | black_pawns),
+            outposts: per_color(outposts),
+        }
+    }
+}
+
+impl Position {
+    #[must_use]
+    pub fn pawn_structure(&self) -> PawnStructure {
+        PawnStructure::new(
+            self.pieces[Color::White as usize][Piece::Pawn as usize],
+            self.pieces[Color::Black as usize][Piece::Pawn as usize],
+        )
+    }
+}
+
+/// Extends every square towards rank 8.
+#[must_use]
+pub fn north_fill(bb: Bitboard) -> Bitboard {
+    let mut bb = bb.0;
+    bb |= bb << 8;
+    bb |= bb << 16;
+    bb |= bb << 32;
+    Bitboard(bb)
+}
+
+/// Extends every square towards rank 1.
+#[must_use]
+pub fn south_fill(bb: Bitboard) -> Bitboard {
+    let mut bb = bb.0;
+    bb |= bb >> 8;
+    bb |= bb >> 16;
+    bb |= bb >> 32;
+    Bitboard(bb)
+}
+
+/// Whole files of every square.
+#[must_use]
+pub fn file_fill(bb: Bitboard) -> Bitboard {
+    north_fill(bb) | south_fill(bb)
+}
+
+/// Squares in front of the pawns on their files, from the point of view of `color`.
+#[must_use]
+pub fn front_span(pawns: Bitboard, color: Color) -> Bitboard {
+    match color {
+        Color::White => north_fill(pawns.shift(&Direction::North)),
+        Color::Black => south_fill(pawns.shift(&Direction::South)),
+    }
+}
+
+/// Squares behind the pawns on their files, from the point of view of `color`.
+#[must_use]
+pub fn rear_span(pawns: Bitboard, color: Color) -> Bitboard {
+    front_span(pawns, color.enemy())
+}
+
+/// Squares the pawns attack now or after advancing.
+#[must_use]
+pub fn attack_span(pawns: Bitboard, color: Color) -> Bitboard {
+    let span = front_span(pawns, color);
+    span.shift(&Direction::East) | span.shift(&Direction::West)
+}
+
+/// Pawns no enemy pawn can stop or capture on their way to promotion.
+#[must_use]
+pub fn passed(pawns: Bitboard, enemy_pawns: Bitboard, color: Color) -> Bitboard {
+    let enemy = color.enemy();
+    pawns & !(front_span(enemy_pawns, enemy) | attack_span(enemy_pawns, enemy))
+}
+
+/// Pawns without pawns of the same color on the adjacent files.
+#[must_use]
+pub fn isolated(pawns: Bitboard) -> Bitboard {
+    let files = file_fill(pawns);
+    pawns & !(files.shift(&Direction::East) | files.shift(&Direction::West))
+}
+
+#[must_use]
+pub fn doubled(pawns: Bitboard) -> Bitboard {
+    pawns
+        & (north_fill(pawns.shift(&Direction::North)) | south_fill(pawns.shift(&Direction::South)))
+}
+
+/// Pawns whose stop square is attacked by an enemy pawn and can't be defended by advancing
+/// their neighbours.
+#[must_use]
+pub fn backward(pawns: Bitboard, enemy_pawns: Bitboard, color: Color) -> Bitboard {
+    let (forward, backward) = match color {
+        Color::White => (Direction::North, Direction::South),
+        Color::Black => (Direction::South, Direction::North),
+    };
+
+    let stops = pawns.shift(&forward);
+    let enemy_attacks = mask_pawns_attacks(enemy_pawns, &color.enemy());
+
+    (stops & enemy_attacks & !attack_span(pawns, color)).shift(&backward)
+}
+
+#[must_use]
+pub fn connected(pawns: Bitboard, color: Color) -> Bitboard {
+    let defended = mask_pawns_attacks(pawns, &color);
+    let phalanx = pawns.shift(&Direction::East) | pawns.shift(&Direction::West);
+
+    pawns & (defended | phalanx)
+}
+
+/// Pawns on half-open files that are not passed yet, but have at least as many friendly pawns
+/// on the adjacent files level with or behind them as there are enemy pawns controlling their
+/// way forward.
+#[must_use]
+pub fn candidate(pawns: Bitboard, enemy_pawns: Bitboard, color: Color) -> Bitboard {
+    let enemy = color.enemy();
+    let on_half_open_file = pawns & !front_span(enemy_pawns, enemy);
+    let not_passed = on_half_open_file & !passed(pawns, enemy_pawns, color);
+
+    let mut candidates = Bitboard::empty();
+    for square in not_passed {
+        let pawn = square.bitboard();
+        let sentries = attack_span(pawn, color) & enemy_pawns;
+
+        let level = pawn.shift(&Direction::East) | pawn.shift(&Direction::West);
+        let helpers = (attack_span(pawn, enemy) | level) & pawns;
+
+        if helpers.count() >= sentries.count() {
+            candidates |= pawn;
+        }
+    }
+
+    candidates
+}
+
+/// Files without pawns, as full file bitboards.
+#[must_use]
+pub fn open_files(all_pawns: Bitboard) -> Bitboard {
+    !file_fill(all_pawns)
+}
+
+/// Files without own pawns, but with enemy pawns, as full file bitboards.
+#[must_use]
+pub fn half_open_files(pawns: Bitboard, enemy_pawns: Bitboard) -> Bitboard {
+    !file_fill(pawns) & file_fill(enemy_pawns)
+}
+
+/// Squares on the 4th to 6th rank from the point of view of `color`, defended by a pawn and out
+/// of reach of the enemy pawns.
+#[must_use]
+pub fn outposts(pawns: Bitboard, enemy_pawns: Bitboard, color: Color) -> Bitboard {
+    let ranks = match color {
+        Color::White => Rank::R4.bitboard() | Rank::R5.bitboard() | Rank::R6.bitboard(),
+        Color::Black => Rank::R3.bitboard() | Rank::R4.bitboard() | Rank::R5.bitboard(),
+    };
+
+    ranks & mask_pawns_attacks(pawns, &color) & !attack_span(enemy_pawns, color.enemy())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::Bitboard,
+        fen::Fen,
+        pawn_structure::{file_fill, front_span, rear_span},
+        position::{Color, Position},
+        square::{File, Square},
+    };
+
+    fn squares(squares: &[Square]) -> Bitboard {
+        squares
+            .iter()
+            .fold(Bitboard::empty(), |bb, square| bb | square.bitboard())
+    }
+
+    #[test]
+    fn test_spans() {
+        let pawn = Square::D4.bitboard();
+
+        assert_eq!(
+            front_span(pawn, Color::White),
+            squares(&[Square::D5, Square::D6, Square::D7, Square::D8])
+        );
+        assert_eq!(
+            rear_span(pawn, Color::White),
+            squares(&[Square::D1, Square::D2, Square::D3])
+        );
+        assert_eq!(
+            front_span(pawn, Color::Black),
+            rear_span(pawn, Color::White)
+        );
+        assert_eq!(file_fill(pawn), File::D.bitboard());
+    }
+
+    #[test]
+    fn test_pawn_structure() {
+        // White: doubled a-pawns, passed c3, candidate d5 and a backward, isolated f4.
+        // Black: f7 defends e6 and g6.
+        let pos =
+            Position::from_fen("4k3/5p2/4p1p1/3P4/P4P2/P1P5/8/4K3 w - - 0 1".to_string()).unwrap();
+        let structure = pos.pawn_structure();
+        let (white, black) = (Color::White as usize, Color::Black as usize);
+
+        assert_eq!(
+            structure.isolated[white],
+            squares(&[Square::A3, Square::A4, Square::F4])
+        );
+        assert_eq!(structure.doubled[white], squares(&[Square::A3, Square::A4]));
+        assert_eq!(
+            structure.passed[white],
+            squares(&[Square::A3, Square::A4, Square::C3])
+        );
+        assert_eq!(structure.candidate[white], squares(&[Square::D5]));
+        assert_eq!(
+            structure.outposts[white],
+            squares(&[Square::B4, Square::B5, Square::C6])
+        );
+        assert_eq!(structure.passed[black], Bitboard::empty());
+        assert_eq!(
+            structure.connected[black],
+            squares(&[Square::E6, Square::G6])
+        );
+        assert_eq!(structure.backward[white], squares(&[Square::F4]));
+        assert_eq!(
+            structure.open_files,
+            File::B.bitboard() | File::H.bitboard()
+        );
+        assert_eq!(
+            structure.half_open_files[white],
+            File::E.bitboard() | File::G.bitboard()
+        );
+        assert_eq!(
+            structure.half_open_files[black],
+            File::A.bitboard() | File::C.bitboard() | File::D.bitboard()
+        );
+    }
+}
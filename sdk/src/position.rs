@@ -1,31 +1,45 @@
-use std::{fmt::Display, str::FromStr};
+use alloc::string::ToString;
+use core::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
 use anyhow::anyhow;
-use derivative::Derivative;
 
 use crate::{
     bitboard::Bitboard,
+    error::ParseError,
     fen::Fen,
-    square::Square,
+    lookup::{
+        king::mask_king_attacks,
+        knights::mask_knights_attacks,
+        pawns::mask_pawns_attacks,
+        sliders::{mask_slider_attacks_occ, Slider},
+    },
+    square::{File, Rank, Square},
+    zobrist::{castling_key, en_passant_key, piece_key, PIECE_KEYS, SIDE_KEY},
 };
 
-#[derive(Derivative)]
-#[derive(Debug, Clone)]
-#[derivative(Hash)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Position {
     pub pieces: [[Bitboard; 6]; 2],
+    /// Piece on each square, kept in sync with `pieces` for constant time lookups.
+    pub board: [Option<(Piece, Color)>; 64],
+    /// Occupancy of each color.
+    pub colors: [Bitboard; 2],
     pub occupied: Bitboard,
     pub turn: Color,
-    #[derivative(Hash = "ignore")]
-    #[derivative(PartialEq = "ignore")]
     pub castling: Castling,
     pub en_passant: Option<Square>,
-    #[derivative(Hash = "ignore")]
-    #[derivative(PartialEq = "ignore")]
     pub halfmove_clock: u16,
-    #[derivative(Hash = "ignore")]
-    #[derivative(PartialEq = "ignore")]
     pub fullmove_number: u16,
+    /// Zobrist key of pieces, side to move, castling rights and en passant file.
+    pub hash: u64,
+    /// Zobrist key of pawns only.
+    pub pawn_hash: u64,
+    /// Zobrist key of piece counts only.
+    pub material_hash: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
@@ -44,11 +58,14 @@ pub enum Piece {
     King,
 }
 
-#[derive(Debug, Clone, Hash)]
+/// Castling rights, stored as the file of the castling rook for each [`CastlingKind`], so
+/// Chess960 positions can be represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Castling {
-    inner: u8,
+    rooks: [Option<File>; 4],
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CastlingKind {
     WhiteKingside,
     WhiteQueenside,
@@ -56,119 +73,231 @@ pub enum CastlingKind {
     BlackQueenside,
 }
 
+/// Parses `KQkq` as in standard chess and file letters as in Shredder-FEN. Without the board
+/// the king is assumed to stand on the e-file, use [`crate::fen::Fen::from_fen`] to resolve the
+/// rights of Chess960 positions.
 impl FromStr for Castling {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s == "-" {
-            return Ok(Castling { inner: 0 });
+            return Ok(Castling::empty());
         }
 
-        let mut inner = 0u8;
+        let mut castling = Castling::empty();
 
         for c in s.chars() {
-            match c {
-                'K' => inner |= 0b1000,
-                'Q' => inner |= 0b0100,
-                'k' => inner |= 0b0010,
-                'q' => inner |= 0b0001,
-                _ => return Err(anyhow!("Invalid castling string: {}", s)),
+            let color = if c.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+
+            let (kind, file) = match c.to_ascii_lowercase() {
+                'k' => (CastlingKind::new(color, true), File::H),
+                'q' => (CastlingKind::new(color, false), File::A),
+                c @ 'a'..='h' => {
+                    let file = File::ALL[(c as u8 - b'a') as usize];
+                    (CastlingKind::new(color, file > File::E), file)
+                }
+                _ => return Err(ParseError::InvalidCastling(s.to_string())),
+            };
+
+            if castling.has_castling_kind(&kind) {
+                return Err(ParseError::InvalidCastling(s.to_string()));
             }
+            castling.add_castling_rook(&kind, file);
         }
 
-        Ok(Castling { inner })
+        if castling.is_empty() {
+            return Err(ParseError::InvalidCastling(s.to_string()));
+        }
+
+        Ok(castling)
     }
 }
 
-impl From<Square> for CastlingKind {
-    fn from(value: Square) -> Self {
-        match value {
-            Square::G1 => CastlingKind::WhiteKingside,
-            Square::C1 => CastlingKind::WhiteQueenside,
-            Square::G8 => CastlingKind::BlackKingside,
-            Square::C8 => CastlingKind::BlackQueenside,
-            _ => panic!("Invalid castling square: {value}"),
+impl CastlingKind {
+    #[must_use]
+    pub const fn all() -> [CastlingKind; 4] {
+        [
+            CastlingKind::WhiteKingside,
+            CastlingKind::WhiteQueenside,
+            CastlingKind::BlackKingside,
+            CastlingKind::BlackQueenside,
+        ]
+    }
+
+    #[must_use]
+    pub fn new(color: Color, kingside: bool) -> CastlingKind {
+        match (color, kingside) {
+            (Color::White, true) => CastlingKind::WhiteKingside,
+            (Color::White, false) => CastlingKind::WhiteQueenside,
+            (Color::Black, true) => CastlingKind::BlackKingside,
+            (Color::Black, false) => CastlingKind::BlackQueenside,
         }
     }
-}
 
-impl CastlingKind {
-    /// Returns tuple of `(rook_target_square, king_target_square)`.
     #[must_use]
-    pub fn target_squares(&self) -> (Square, Square) {
+    pub fn color(&self) -> Color {
         match self {
-            CastlingKind::WhiteKingside => (Square::E1, Square::G1),
-            CastlingKind::WhiteQueenside => (Square::E1, Square::C1),
-            CastlingKind::BlackKingside => (Square::E8, Square::G8),
-            CastlingKind::BlackQueenside => (Square::E8, Square::C8),
+            CastlingKind::WhiteKingside | CastlingKind::WhiteQueenside => Color::White,
+            CastlingKind::BlackKingside | CastlingKind::BlackQueenside => Color::Black,
         }
     }
 
-    /// Returns tuple of `(rook_from_square, king_from_square)`.
     #[must_use]
-    pub fn from_squares(&self) -> (Square, Square) {
+    pub fn is_kingside(&self) -> bool {
+        matches!(
+            self,
+            CastlingKind::WhiteKingside | CastlingKind::BlackKingside
+        )
+    }
+
+    #[must_use]
+    pub fn back_rank(&self) -> Rank {
+        match self.color() {
+            Color::White => Rank::R1,
+            Color::Black => Rank::R8,
+        }
+    }
+
+    /// File of the castling rook in the standard starting position.
+    #[must_use]
+    pub fn standard_rook_file(&self) -> File {
+        if self.is_kingside() {
+            File::H
+        } else {
+            File::A
+        }
+    }
+
+    /// Returns tuple of `(rook_target_square, king_target_square)`, the same in standard chess
+    /// and Chess960.
+    #[must_use]
+    pub fn target_squares(&self) -> (Square, Square) {
         match self {
-            CastlingKind::WhiteKingside => (Square::H1, Square::E1),
-            CastlingKind::WhiteQueenside => (Square::A1, Square::E1),
-            CastlingKind::BlackKingside => (Square::H8, Square::E8),
-            CastlingKind::BlackQueenside => (Square::A8, Square::E8),
+            CastlingKind::WhiteKingside => (Square::F1, Square::G1),
+            CastlingKind::WhiteQueenside => (Square::D1, Square::C1),
+            CastlingKind::BlackKingside => (Square::F8, Square::G8),
+            CastlingKind::BlackQueenside => (Square::D8, Square::C8),
         }
     }
 }
 
 impl Castling {
+    /// Mask of the rights held, used to index the zobrist keys.
+    #[must_use]
+    pub(crate) fn bits(self) -> u8 {
+        CastlingKind::all()
+            .iter()
+            .filter(|kind| self.has_castling_kind(kind))
+            .fold(0, |bits, kind| bits | (0b1000 >> *kind as u8))
+    }
+
+    /// Swaps the rights of white and black.
+    #[must_use]
+    pub fn color_flipped(self) -> Castling {
+        let [white_kingside, white_queenside, black_kingside, black_queenside] = self.rooks;
+        Castling {
+            rooks: [
+                black_kingside,
+                black_queenside,
+                white_kingside,
+                white_queenside,
+            ],
+        }
+    }
+
     #[must_use]
     pub fn full() -> Castling {
-        Castling { inner: 0b1111 }
+        Castling {
+            rooks: [Some(File::H), Some(File::A), Some(File::H), Some(File::A)],
+        }
     }
 
     #[must_use]
     pub fn empty() -> Castling {
-        Castling { inner: 0 }
+        Castling { rooks: [None; 4] }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rooks.iter().all(Option::is_none)
+    }
+
+    /// Whether every right uses the rook of the standard starting position.
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        CastlingKind::all().iter().all(|kind| {
+            self.rook_file(kind)
+                .is_none_or(|file| file == kind.standard_rook_file())
+        })
     }
 
     #[must_use]
     pub fn has_castling_kind(&self, castling_kind: &CastlingKind) -> bool {
-        match castling_kind {
-            CastlingKind::WhiteKingside => self.inner & 0b1000 != 0,
-            CastlingKind::WhiteQueenside => self.inner & 0b0100 != 0,
-            CastlingKind::BlackKingside => self.inner & 0b0010 != 0,
-            CastlingKind::BlackQueenside => self.inner & 0b0001 != 0,
-        }
+        self.rooks[*castling_kind as usize].is_some()
+    }
+
+    #[must_use]
+    pub fn rook_file(&self, castling_kind: &CastlingKind) -> Option<File> {
+        self.rooks[*castling_kind as usize]
+    }
+
+    /// Square the castling rook has to stand on for the right to be usable.
+    #[must_use]
+    pub fn rook_square(&self, castling_kind: &CastlingKind) -> Option<Square> {
+        self.rook_file(castling_kind)
+            .map(|file| Square::from((file, castling_kind.back_rank())))
     }
 
     pub fn remove_color_castling(&mut self, color: &Color) {
-        match color {
-            Color::White => self.inner &= 0b0011,
-            Color::Black => self.inner &= 0b1100,
+        for kind in CastlingKind::all() {
+            if kind.color() == *color {
+                self.remove_castling_kind(&kind);
+            }
         }
     }
 
     pub fn remove_castling_kind(&mut self, castling_kind: &CastlingKind) {
-        match castling_kind {
-            CastlingKind::WhiteKingside => self.inner &= 0b0111,
-            CastlingKind::WhiteQueenside => self.inner &= 0b1011,
-            CastlingKind::BlackKingside => self.inner &= 0b1101,
-            CastlingKind::BlackQueenside => self.inner &= 0b1110,
-        }
+        self.rooks[*castling_kind as usize] = None;
     }
 
+    /// Adds the right with the rook of the standard starting position.
     pub fn add_castling_kind(&mut self, castling_kind: &CastlingKind) {
-        match castling_kind {
-            CastlingKind::WhiteKingside => self.inner |= 0b1000,
-            CastlingKind::WhiteQueenside => self.inner |= 0b0100,
-            CastlingKind::BlackKingside => self.inner |= 0b0010,
-            CastlingKind::BlackQueenside => self.inner |= 0b0001,
-        }
+        self.add_castling_rook(castling_kind, castling_kind.standard_rook_file());
+    }
+
+    /// Adds the right with the rook on `file`, as in Chess960.
+    pub fn add_castling_rook(&mut self, castling_kind: &CastlingKind, file: File) {
+        self.rooks[*castling_kind as usize] = Some(file);
     }
 }
 
 impl Position {
+    /// A board without any pieces, white to move. Not a valid position on its own.
+    #[must_use]
+    pub fn empty() -> Position {
+        Position {
+            pieces: [[Bitboard::empty(); 6]; 2],
+            board: [None; 64],
+            colors: [Bitboard::empty(); 2],
+            occupied: Bitboard::empty(),
+            turn: Color::White,
+            castling: Castling::empty(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            pawn_hash: 0,
+            material_hash: 0,
+        }
+    }
+
     #[must_use]
     pub fn occupation(&self, color: &Color) -> Bitboard {
-        self.pieces[*color as usize]
-            .iter()
-            .fold(Bitboard(0), |acc, x| acc | *x)
+        self.colors[*color as usize]
     }
 
     #[must_use]
@@ -185,14 +314,27 @@ impl Position {
             Color::White => self.turn = Color::Black,
             Color::Black => self.turn = Color::White,
         }
+        self.hash ^= SIDE_KEY;
 
         self.turn
     }
 
+    pub fn set_castling(&mut self, castling: Castling) {
+        self.hash ^= castling_key(self.castling) ^ castling_key(castling);
+        self.castling = castling;
+    }
+
+    pub fn set_en_passant(&mut self, en_passant: Option<Square>) {
+        self.hash ^= en_passant_key(self.en_passant) ^ en_passant_key(en_passant);
+        self.en_passant = en_passant;
+    }
+
     pub fn remove_piece_at(&mut self, square: &Square) -> Option<(Piece, Color)> {
-        let (piece, color) = self.piece_at(square)?;
+        let (piece, color) = self.board[*square as usize].take()?;
 
-        self.pieces[color as usize][piece as usize] ^= square.bitboard();
+        self.toggle_piece(piece, color, *square);
+        let count = self.pieces[color as usize][piece as usize].count();
+        self.material_hash ^= PIECE_KEYS[color as usize][piece as usize][count as usize];
 
         Some((piece, color))
     }
@@ -206,37 +348,124 @@ impl Position {
         if self.piece_at(&square).is_some() {
             return Err(anyhow!("Piece already at {}", square.coords_str()));
         }
-        self.pieces[color as usize][piece as usize] |= Into::<Bitboard>::into(square);
+        let count = self.pieces[color as usize][piece as usize].count();
+        self.material_hash ^= PIECE_KEYS[color as usize][piece as usize][count as usize];
+        self.board[square as usize] = Some((piece, color));
+        self.toggle_piece(piece, color, square);
 
         Ok(())
     }
 
-    #[must_use]
-    pub fn piece_at(&self, square: &Square) -> Option<(Piece, Color)> {
-        let square = square.bitboard();
-        for color in [Color::White, Color::Black] {
-            for (i, piece_bb) in self.pieces[color as usize].iter().enumerate() {
-                if !((piece_bb & square).is_empty()) {
-                    return Some((Piece::from(i), color));
+    /// Moves the piece at `from` to the empty square `to`. Cheaper than removing and adding it, as
+    /// the material key doesn't change.
+    pub fn move_piece(
+        &mut self,
+        from: Square,
+        to: Square,
+    ) -> Result<(Piece, Color), anyhow::Error> {
+        if self.piece_at(&to).is_some() {
+            return Err(anyhow!("Piece already at {}", to.coords_str()));
+        }
+        let (piece, color) = self.board[from as usize]
+            .take()
+            .ok_or(anyhow!("No piece at {}", from.coords_str()))?;
+
+        self.board[to as usize] = Some((piece, color));
+        self.toggle_piece(piece, color, from);
+        self.toggle_piece(piece, color, to);
+
+        Ok((piece, color))
+    }
+
+    /// Toggles a piece in the bitboards and the piece-square keys, leaving `board` to the caller.
+    fn toggle_piece(&mut self, piece: Piece, color: Color, square: Square) {
+        let bb = square.bitboard();
+        self.pieces[color as usize][piece as usize] ^= bb;
+        self.colors[color as usize] ^= bb;
+        self.occupied ^= bb;
+
+        let key = piece_key(piece, color, square);
+        self.hash ^= key;
+        if piece == Piece::Pawn {
+            self.pawn_hash ^= key;
+        }
+    }
+
+    /// Rebuilds `board`, `colors` and `occupied` from `pieces`. Needed after modifying `pieces`
+    /// directly.
+    pub fn refresh_occupancy(&mut self) {
+        self.board = [None; 64];
+        self.colors = [Bitboard::empty(); 2];
+        for color in Color::iter() {
+            for piece in Piece::all() {
+                let bb = self.pieces[color as usize][piece as usize];
+                for square in bb {
+                    self.board[square as usize] = Some((piece, color));
                 }
+                self.colors[color as usize] |= bb;
             }
         }
+        self.occupied = self.colors[Color::White as usize] | self.colors[Color::Black as usize];
+    }
+
+    /// Castling right that a rook of `color` on `square` would belong to, found by the side of
+    /// the king it stands on. `None` unless both stand on the back rank of `color`.
+    #[must_use]
+    pub fn castling_kind_for_rook(&self, square: Square, color: Color) -> Option<CastlingKind> {
+        let back_rank = CastlingKind::new(color, true).back_rank();
+        let king = self.pieces[color as usize][Piece::King as usize];
+        if king.count() != 1 || king.lsb().rank() != back_rank || square.rank() != back_rank {
+            return None;
+        }
+
+        let king_file = king.lsb().file();
+        (square.file() != king_file).then(|| CastlingKind::new(color, square.file() > king_file))
+    }
 
-        None
+    /// Iterates over all pieces on the board, from A1 to H8.
+    pub fn pieces(&self) -> impl Iterator<Item = (Square, Piece, Color)> + '_ {
+        self.occupied.into_iter().filter_map(|square| {
+            self.board[square as usize].map(|(piece, color)| (square, piece, color))
+        })
+    }
+
+    /// Pieces of `by` attacking `square`. Computed without lookup tables, so it is much slower than
+    /// `MoveGen::attacks_to_square` and meant for validation rather than search.
+    #[must_use]
+    pub fn attackers(&self, square: Square, by: Color) -> Bitboard {
+        let pieces = &self.pieces[by as usize];
+        let target = square.bitboard();
+
+        let rook_queens = pieces[Piece::Rook as usize] | pieces[Piece::Queen as usize];
+        let bishop_queens = pieces[Piece::Bishop as usize] | pieces[Piece::Queen as usize];
+
+        (mask_pawns_attacks(target, &by.enemy()) & pieces[Piece::Pawn as usize])
+            | (mask_knights_attacks(target) & pieces[Piece::Knight as usize])
+            | (mask_king_attacks(target) & pieces[Piece::King as usize])
+            | (mask_slider_attacks_occ(Slider::Rook, self.occupied, square) & rook_queens)
+            | (mask_slider_attacks_occ(Slider::Bishop, self.occupied, square) & bishop_queens)
+    }
+
+    #[must_use]
+    pub fn piece_at(&self, square: &Square) -> Option<(Piece, Color)> {
+        self.board[*square as usize]
     }
 }
 
-impl From<usize> for Piece {
-    fn from(value: usize) -> Self {
-        match value {
-            0 => Piece::Pawn,
-            1 => Piece::Knight,
-            2 => Piece::Bishop,
-            3 => Piece::Rook,
-            4 => Piece::Queen,
-            5 => Piece::King,
-            _ => panic!("Invalid piece index"),
-        }
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl TryFrom<usize> for Piece {
+    type Error = ParseError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Piece::all()
+            .get(value)
+            .copied()
+            .ok_or(ParseError::InvalidPieceIndex(value))
     }
 }
 
@@ -248,7 +477,7 @@ impl Default for Position {
 }
 
 impl Display for Color {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Color::White => write!(f, "w"),
             Color::Black => write!(f, "b"),
@@ -256,22 +485,29 @@ impl Display for Color {
     }
 }
 
+/// Writes `KQkq` for the standard rooks and the file of any other rook, as in X-FEN. Use
+/// [`crate::fen::Fen::to_fen`] to write the rights of a Chess960 position.
 impl Display for Castling {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.has_castling_kind(&CastlingKind::WhiteKingside) {
-            write!(f, "K")?;
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
         }
 
-        if self.has_castling_kind(&CastlingKind::WhiteQueenside) {
-            write!(f, "Q")?;
-        }
+        for kind in CastlingKind::all() {
+            let Some(file) = self.rook_file(&kind) else {
+                continue;
+            };
 
-        if self.has_castling_kind(&CastlingKind::BlackKingside) {
-            write!(f, "k")?;
-        }
+            let symbol = match (file == kind.standard_rook_file(), kind.is_kingside()) {
+                (true, true) => 'k',
+                (true, false) => 'q',
+                (false, _) => (b'a' + file as u8) as char,
+            };
 
-        if self.has_castling_kind(&CastlingKind::BlackQueenside) {
-            write!(f, "q")?;
+            match kind.color() {
+                Color::White => write!(f, "{}", symbol.to_ascii_uppercase())?,
+                Color::Black => write!(f, "{symbol}")?,
+            }
         }
 
         Ok(())
@@ -279,7 +515,7 @@ impl Display for Castling {
 }
 
 impl Display for Piece {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Piece::Pawn => write!(f, "p"),
             Piece::Knight => write!(f, "n"),
@@ -292,7 +528,7 @@ impl Display for Piece {
 }
 
 impl Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(f)?;
         for rank in (0..8u8).rev() {
             for file in 0..8u8 {
@@ -350,16 +586,31 @@ impl Piece {
     }
 }
 
-impl From<String> for Piece {
-    fn from(value: String) -> Self {
-        match value.to_lowercase().as_str() {
-            "p" => Piece::Pawn,
-            "n" => Piece::Knight,
-            "b" => Piece::Bishop,
-            "r" => Piece::Rook,
-            "q" => Piece::Queen,
-            "k" => Piece::King,
-            _ => panic!("Invalid piece symbol"),
+impl FromStr for Piece {
+    type Err = ParseError;
+
+    /// Parses a piece letter in either case, e.g. `"n"` or `"N"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "p" | "P" => Ok(Piece::Pawn),
+            "n" | "N" => Ok(Piece::Knight),
+            "b" | "B" => Ok(Piece::Bishop),
+            "r" | "R" => Ok(Piece::Rook),
+            "q" | "Q" => Ok(Piece::Queen),
+            "k" | "K" => Ok(Piece::King),
+            _ => Err(ParseError::InvalidPiece(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "w" => Ok(Color::White),
+            "b" => Ok(Color::Black),
+            _ => Err(ParseError::InvalidColor(s.to_string())),
         }
     }
 }
@@ -395,3 +646,55 @@ impl Color {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::Bitboard,
+        position::{Color, Piece, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_board_stays_in_sync() {
+        let mut pos = Position::default();
+
+        assert_eq!(pos.piece_at(&Square::E1), Some((Piece::King, Color::White)));
+        assert_eq!(pos.pieces().count(), 32);
+
+        pos.move_piece(Square::G1, Square::F3).unwrap();
+        assert!(pos.move_piece(Square::G1, Square::H3).is_err());
+        assert!(pos.move_piece(Square::F3, Square::E2).is_err());
+        pos.remove_piece_at(&Square::D8);
+
+        assert_eq!(pos.piece_at(&Square::G1), None);
+        assert_eq!(pos.piece_at(&Square::F3), Some((Piece::Knight, Color::White)));
+        assert_eq!(pos.occupation(&Color::Black).count(), 15);
+        assert_eq!(
+            pos.occupied,
+            pos.occupation(&Color::White) | pos.occupation(&Color::Black)
+        );
+        assert_eq!(pos.hash, pos.compute_hash());
+
+        let squares = pos
+            .pieces()
+            .filter(|(_, piece, _)| *piece == Piece::Knight)
+            .map(|(square, _, color)| (square, color))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            squares,
+            vec![
+                (Square::B1, Color::White),
+                (Square::F3, Color::White),
+                (Square::B8, Color::Black),
+                (Square::G8, Color::Black)
+            ]
+        );
+
+        let mut poked = pos.clone();
+        poked.pieces[Color::White as usize][Piece::Queen as usize] = Bitboard::empty();
+        poked.refresh_occupancy();
+        assert_eq!(poked.piece_at(&Square::D1), None);
+        assert!(!poked.occupied.has(Square::D1));
+    }
+}
@@ -0,0 +1,287 @@
+use alloc::vec::Vec;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    bitboard::Bitboard,
+    material::MaterialSignature,
+    position::{Castling, CastlingKind, Color, Piece, Position},
+    square::{File, Rank, Square},
+};
+
+/// Maximum random count of each piece per side, used when no material is given.
+const MAX_RANDOM_COUNTS: [u8; 6] = [8, 2, 2, 2, 1, 1];
+
+/// Constraints for random positions. `None` leaves the property to chance.
+///
+/// Positions are built by placing the requested pieces at random and retrying until the result
+/// passes [`Position::validate`] and every constraint holds.
+#[derive(Debug, Clone)]
+pub struct RandomPosition {
+    pub material: Option<MaterialSignature>,
+    pub turn: Option<Color>,
+    /// Whether any castling right is present.
+    pub castling: Option<bool>,
+    /// Whether an en passant square is present.
+    pub en_passant: Option<bool>,
+    /// Whether the side to move is in check.
+    pub in_check: Option<bool>,
+    /// Attempts before [`RandomPosition::generate`] gives up.
+    pub max_attempts: usize,
+}
+
+impl Default for RandomPosition {
+    fn default() -> Self {
+        RandomPosition {
+            material: None,
+            turn: None,
+            castling: None,
+            en_passant: None,
+            in_check: None,
+            max_attempts: 10_000,
+        }
+    }
+}
+
+impl RandomPosition {
+    /// Returns `None` if no position satisfying the constraints was found within
+    /// `max_attempts` attempts, e.g. because they are contradictory.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Position> {
+        (0..self.max_attempts).find_map(|_| self.try_generate(rng))
+    }
+
+    fn try_generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Position> {
+        let mut counts = [[0u8; 6]; 2];
+        for color in Color::iter() {
+            counts[color as usize] = match self.material {
+                Some(material) => Piece::all().map(|piece| material.count(color, piece)),
+                None => MAX_RANDOM_COUNTS.map(|max| rng.gen_range(0..=max)),
+            };
+            counts[color as usize][Piece::King as usize] = 1;
+        }
+
+        let turn = self.turn.unwrap_or_else(|| {
+            if rng.gen() {
+                Color::White
+            } else {
+                Color::Black
+            }
+        });
+
+        let mut position = Position::empty();
+        let mut reserved = Bitboard::empty();
+
+        if self.castling.unwrap_or_else(|| rng.gen_ratio(1, 4)) {
+            let mut kinds = CastlingKind::all().to_vec();
+            kinds.shuffle(rng);
+            let kept = rng.gen_range(1..=kinds.len());
+
+            let mut castling = Castling::empty();
+            for kind in &kinds[..kept] {
+                let color = kind.color();
+                let king_square = Square::from((File::E, kind.back_rank()));
+                let rook_square = Square::from((kind.standard_rook_file(), kind.back_rank()));
+
+                let king_placed = position.piece_at(&king_square) == Some((Piece::King, color));
+                if !king_placed
+                    && !place(&mut position, &mut counts, king_square, Piece::King, color)
+                {
+                    continue;
+                }
+                if place(&mut position, &mut counts, rook_square, Piece::Rook, color) {
+                    castling.add_castling_kind(kind);
+                }
+            }
+
+            if castling.is_empty() {
+                return None;
+            }
+            position.castling = castling;
+        }
+
+        if self.en_passant.unwrap_or_else(|| rng.gen_ratio(1, 4)) {
+            // The side not to move has just pushed a pawn by two squares.
+            let pushed = turn.enemy();
+            let (rank, direction) = match pushed {
+                Color::White => (Rank::R4, -1),
+                Color::Black => (Rank::R5, 1),
+            };
+
+            let file = rng.gen_range(0..8u8);
+            let square = Square::try_from(rank as u8 * 8 + file).ok()?;
+            let en_passant = square.offset(direction, 0)?;
+            let origin = en_passant.offset(direction, 0)?;
+
+            if !place(&mut position, &mut counts, square, Piece::Pawn, pushed) {
+                return None;
+            }
+
+            // Moves only set the en passant square if a pawn can take on it.
+            let capture_file = if rng.gen() { -1 } else { 1 };
+            let capturer = square
+                .offset(0, capture_file)
+                .or_else(|| square.offset(0, -capture_file))?;
+            if !place(&mut position, &mut counts, capturer, Piece::Pawn, turn) {
+                return None;
+            }
+            reserved |= en_passant.bitboard() | origin.bitboard();
+            position.en_passant = Some(en_passant);
+        }
+
+        let back_ranks = Rank::R1.bitboard() | Rank::R8.bitboard();
+        for color in Color::iter() {
+            for piece in Piece::all() {
+                while counts[color as usize][piece as usize] > 0 {
+                    let mut forbidden = position.occupied | reserved;
+                    if piece == Piece::Pawn {
+                        forbidden |= back_ranks;
+                    }
+
+                    let free = (!forbidden).into_iter().collect::<Vec<_>>();
+                    let square = *free.choose(rng)?;
+                    place(&mut position, &mut counts, square, piece, color);
+                }
+            }
+        }
+
+        position.turn = turn;
+        position.refresh_hashes();
+
+        if position.validate().is_err() {
+            return None;
+        }
+
+        if let Some(in_check) = self.in_check {
+            let king = position.pieces[turn as usize][Piece::King as usize].lsb();
+            if position.attackers(king, turn.enemy()).is_empty() == in_check {
+                return None;
+            }
+        }
+
+        Some(position)
+    }
+}
+
+/// Places a piece if any of its count is left and the square is empty.
+fn place(
+    position: &mut Position,
+    counts: &mut [[u8; 6]; 2],
+    square: Square,
+    piece: Piece,
+    color: Color,
+) -> bool {
+    let count = &mut counts[color as usize][piece as usize];
+    if *count == 0 || position.add_piece_at(square, piece, color).is_err() {
+        return false;
+    }
+
+    *count -= 1;
+    true
+}
+
+impl Position {
+    /// Random legal position without constraints, see [`RandomPosition`].
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Position {
+        RandomPosition::default()
+            .generate(rng)
+            .expect("BUG: Unconstrained random position not found")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        fen::Fen,
+        material::MaterialSignature,
+        position::{Color, Piece, Position},
+        random::RandomPosition,
+    };
+
+    fn in_check(pos: &Position) -> bool {
+        let king = pos.pieces[pos.turn as usize][Piece::King as usize].lsb();
+        !pos.attackers(king, pos.enemy()).is_empty()
+    }
+
+    #[test]
+    fn test_random_positions_are_valid() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..500 {
+            let pos = Position::random(&mut rng);
+
+            assert_eq!(pos.validate(), Ok(()), "{}", pos.to_fen());
+            assert_eq!(pos.hash, pos.compute_hash());
+            assert_eq!(Position::from_fen(pos.to_fen()), Ok(pos.clone()));
+        }
+    }
+
+    #[test]
+    fn test_constraints() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let material = MaterialSignature::from_str("KRPvKR").unwrap();
+
+        let constraints = RandomPosition {
+            material: Some(material),
+            turn: Some(Color::Black),
+            castling: Some(false),
+            en_passant: Some(false),
+            in_check: Some(true),
+            ..RandomPosition::default()
+        };
+        for _ in 0..100 {
+            let pos = constraints.generate(&mut rng).unwrap();
+
+            assert_eq!(pos.material_signature(), material);
+            assert_eq!(pos.turn, Color::Black);
+            assert!(pos.castling.is_empty());
+            assert_eq!(pos.en_passant, None);
+            assert!(in_check(&pos), "{}", pos.to_fen());
+        }
+
+        let constraints = RandomPosition {
+            castling: Some(true),
+            en_passant: Some(true),
+            in_check: Some(false),
+            ..RandomPosition::default()
+        };
+        for _ in 0..100 {
+            let pos = constraints.generate(&mut rng).unwrap();
+
+            assert!(!pos.castling.is_empty());
+            assert!(pos.en_passant.is_some());
+            assert!(pos.can_take_en_passant(pos.en_passant.unwrap()));
+            assert!(!in_check(&pos), "{}", pos.to_fen());
+            assert_eq!(pos.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_impossible_constraints() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let constraints = RandomPosition {
+            material: Some(MaterialSignature::from_str("KvK").unwrap()),
+            castling: Some(true),
+            max_attempts: 100,
+            ..RandomPosition::default()
+        };
+
+        assert_eq!(constraints.generate(&mut rng), None);
+    }
+
+    #[test]
+    fn test_seeded() {
+        let generate = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..10)
+                .map(|_| Position::random(&mut rng).to_fen())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(generate(3), generate(3));
+        assert_ne!(generate(3), generate(4));
+    }
+}
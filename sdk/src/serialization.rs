@@ -0,0 +1,152 @@
+//! `serde` support, enabled by the `serde` feature.
+//!
+//! Every type is serialized as the string used in FEN: positions as a full FEN, squares as
+//! `"e4"`, pieces as their letter, colors as `"w"`/`"b"` and castling rights as `"KQkq"`.
+//! Bitboards are serialized as a hex string. Human readable formats may also give them as a list
+//! of squares.
+
+use alloc::string::String;
+use core::{fmt, str::FromStr};
+
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    bitboard::Bitboard,
+    fen::Fen,
+    position::{Castling, Color, Piece, Position},
+    square::Square,
+};
+
+macro_rules! impl_serde_via_str {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = String::deserialize(deserializer)?;
+                    <$ty>::from_str(&value).map_err(de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+impl_serde_via_str!(Square, Piece, Color, Castling);
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_fen())
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fen = String::deserialize(deserializer)?;
+        Position::from_fen(fen).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Bitboard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:#018x}", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Bitboard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Only self-describing formats can tell a string from a list of squares.
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BitboardVisitor)
+        } else {
+            deserializer.deserialize_str(BitboardVisitor)
+        }
+    }
+}
+
+struct BitboardVisitor;
+
+impl<'de> Visitor<'de> for BitboardVisitor {
+    type Value = Bitboard;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a hex string or a list of squares")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Bitboard, E> {
+        let digits = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+
+        u64::from_str_radix(digits, 16)
+            .map(Bitboard)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bitboard, A::Error> {
+        let mut bb = Bitboard::empty();
+        while let Some(square) = seq.next_element::<Square>()? {
+            bb |= square.bitboard();
+        }
+
+        Ok(bb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use crate::{
+        bitboard::Bitboard,
+        fen::Fen,
+        position::{Castling, Color, Piece, Position},
+        square::{Rank, Square},
+    };
+
+    fn round_trip<T>(value: &T, json: &str)
+    where
+        T: Serialize + DeserializeOwned + PartialEq + core::fmt::Debug,
+    {
+        assert_eq!(serde_json::to_string(value).unwrap(), json);
+        assert_eq!(&serde_json::from_str::<T>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(&Square::E4, "\"e4\"");
+        round_trip(&Piece::Knight, "\"n\"");
+        round_trip(&Color::Black, "\"b\"");
+        round_trip(&Castling::full(), "\"KQkq\"");
+        round_trip(&Castling::empty(), "\"-\"");
+        round_trip(&Rank::R2.bitboard(), "\"0x000000000000ff00\"");
+
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let pos = Position::from_fen(fen.to_string()).unwrap();
+        round_trip(&pos, &format!("\"{fen}\""));
+    }
+
+    #[test]
+    fn test_bitboard_from_squares() {
+        let bb: Bitboard = serde_json::from_str("[\"a1\", \"h8\"]").unwrap();
+        assert_eq!(bb, Square::A1.bitboard() | Square::H8.bitboard());
+
+        let bb: Bitboard = serde_json::from_str("\"FF\"").unwrap();
+        assert_eq!(bb, Rank::R1.bitboard());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(serde_json::from_str::<Square>("\"i9\"").is_err());
+        assert!(serde_json::from_str::<Position>("\"8/8 w\"").is_err());
+        assert!(serde_json::from_str::<Bitboard>("\"0xzz\"").is_err());
+    }
+}
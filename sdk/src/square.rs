@@ -1,10 +1,22 @@
-use std::fmt::{Display, Formatter};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
-use crate::bitboard::Bitboard;
+use crate::{
+    bitboard::{Bitboard, Direction},
+    error::ParseError,
+};
 
-#[derive(IntoPrimitive, TryFromPrimitive, Debug, PartialEq, Eq, Ord, PartialOrd)]
+#[derive(
+    IntoPrimitive, TryFromPrimitive, Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd, Hash,
+)]
 #[repr(u8)]
 pub enum File {
     A,
@@ -18,7 +30,7 @@ pub enum File {
 }
 
 impl Display for File {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let c = match self {
             File::A => 'a',
             File::B => 'b',
@@ -35,7 +47,7 @@ impl Display for File {
 }
 
 impl Display for Rank {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let c = match self {
             Rank::R1 => '1',
             Rank::R2 => '2',
@@ -52,6 +64,17 @@ impl Display for Rank {
 }
 
 impl File {
+    pub const ALL: [File; 8] = [
+        File::A,
+        File::B,
+        File::C,
+        File::D,
+        File::E,
+        File::F,
+        File::G,
+        File::H,
+    ];
+
     #[must_use]
     pub const fn bitboard(self) -> Bitboard {
         Bitboard(0x0101_0101_0101_0101 << self as u8)
@@ -59,13 +82,24 @@ impl File {
 }
 
 impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::R1,
+        Rank::R2,
+        Rank::R3,
+        Rank::R4,
+        Rank::R5,
+        Rank::R6,
+        Rank::R7,
+        Rank::R8,
+    ];
+
     #[must_use]
     pub const fn bitboard(self) -> Bitboard {
         Bitboard(0xFF << (self as u8 * 8))
     }
 }
 
-#[derive(IntoPrimitive, TryFromPrimitive, Debug, PartialEq, Eq, Ord, PartialOrd)]
+#[derive(IntoPrimitive, TryFromPrimitive, Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd)]
 #[repr(u8)]
 pub enum Rank {
     R1,
@@ -93,31 +127,91 @@ pub enum Square {
 }
 
 impl Square {
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = [
+        Square::A1, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::H1,
+        Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2,
+        Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3,
+        Square::A4, Square::B4, Square::C4, Square::D4, Square::E4, Square::F4, Square::G4, Square::H4,
+        Square::A5, Square::B5, Square::C5, Square::D5, Square::E5, Square::F5, Square::G5, Square::H5,
+        Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6,
+        Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7,
+        Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
+    ];
+
+    /// Const counterpart of `Square::try_from`, panics if `index` is not below 64.
+    #[must_use]
+    pub const fn from_index(index: u8) -> Square {
+        assert!(index < 64, "BUG: Square index out of bounds.");
+        Square::ALL[index as usize]
+    }
+
     #[must_use]
     pub const fn bitboard(self) -> Bitboard {
         Bitboard(1 << self as usize)
     }
 
     #[must_use]
-    pub fn rank(&self) -> Rank {
-        (*self as u8 >> 3)
-            .try_into()
-            .expect("BUG: Square couldn't be converted to rank.")
+    pub const fn rank(&self) -> Rank {
+        Rank::ALL[(*self as u8 >> 3) as usize]
+    }
+
+    #[must_use]
+    pub const fn file(&self) -> File {
+        File::ALL[(*self as u8 & 0b00_0111) as usize]
     }
 
     #[must_use]
-    pub fn file(&self) -> File {
-        (*self as u8 & 0b00_0111).try_into().expect("Invalid file.")
+    #[allow(clippy::cast_sign_loss)]
+    pub const fn offset(&self, rank_offset: i8, file_offset: i8) -> Option<Square> {
+        let rank = self.rank() as i8 + rank_offset;
+        let file = self.file() as i8 + file_offset;
+
+        if rank < 0 || rank > 7 || file < 0 || file > 7 {
+            return None;
+        }
+
+        Some(Square::from_index((rank * 8 + file) as u8))
     }
 
+    /// Returns the direction of the rank, file or diagonal leading from `self` to `other`, or
+    /// `None` if the squares are equal or not aligned.
     #[must_use]
-    pub fn offset(&self, rank_offset: i8, file_offset: i8) -> Option<Square> {
-        let (file, rank): (File, Rank) = self.into();
+    pub const fn direction_to(self, other: Square) -> Option<Direction> {
+        let rank_diff = other.rank() as i8 - self.rank() as i8;
+        let file_diff = other.file() as i8 - self.file() as i8;
+
+        if (rank_diff == 0 && file_diff == 0)
+            || (rank_diff != 0 && file_diff != 0 && rank_diff.abs() != file_diff.abs())
+        {
+            return None;
+        }
+
+        let directions = Direction::all();
+        let mut idx = 0;
+        while idx < directions.len() {
+            let (rank_step, file_step) = directions[idx].offsets();
+            if rank_step == rank_diff.signum() && file_step == file_diff.signum() {
+                return Some(directions[idx]);
+            }
+            idx += 1;
+        }
 
-        let file = TryInto::<u8>::try_into(file as i8 + file_offset).ok()?;
-        let rank = TryInto::<u8>::try_into(rank as i8 + rank_offset).ok()?;
+        None
+    }
 
-        Some((file.try_into().ok()?, rank.try_into().ok()?).into())
+    #[must_use]
+    pub fn flip_vertical(self) -> Square {
+        (self as u8 ^ 0b11_1000)
+            .try_into()
+            .expect("BUG: Flipped square out of bounds.")
+    }
+
+    #[must_use]
+    pub fn mirror_horizontal(self) -> Square {
+        (self as u8 ^ 0b111)
+            .try_into()
+            .expect("BUG: Mirrored square out of bounds.")
     }
 
     #[must_use]
@@ -137,11 +231,32 @@ impl Square {
 }
 
 impl Display for Square {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.coords_str())
     }
 }
 
+impl FromStr for Square {
+    type Err = ParseError;
+
+    /// Parses coordinates such as `"e4"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseError::InvalidSquare(s.to_string());
+
+        let &[file, rank] = s.as_bytes() else {
+            return Err(error());
+        };
+
+        if !(b'a'..=b'h').contains(&file) || !(b'1'..=b'8').contains(&rank) {
+            return Err(error());
+        }
+
+        ((rank - b'1') * 8 + (file - b'a'))
+            .try_into()
+            .map_err(|_| error())
+    }
+}
+
 impl From<(File, Rank)> for Square {
     fn from((file, rank): (File, Rank)) -> Self {
         let file = file as u8;
@@ -0,0 +1,402 @@
+//! SVG board diagrams, for documentation, bug reports and web pages.
+//!
+//! ```
+//! use sdk::{position::Position, square::Square, svg::{Arrow, BoardSvg}};
+//!
+//! let svg = BoardSvg::new()
+//!     .highlight(Square::E2.bitboard() | Square::E4.bitboard(), "#cdd26a")
+//!     .arrow(Arrow::new(Square::G1, Square::F3))
+//!     .render(&Position::default());
+//! assert!(svg.starts_with("<svg"));
+//! ```
+
+use core::fmt::Write;
+
+use crate::{
+    bitboard::Bitboard,
+    position::{Color, Piece, Position},
+    square::{File, Rank, Square},
+};
+
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+/// Used by [`BoardSvg::render_bitboard`] for the squares in the bitboard.
+const BITBOARD_HIGHLIGHT: &str = "#e4572e";
+const DEFAULT_ARROW: &str = "#15781b";
+
+/// An arrow between the centers of two squares, or a circle if both are the same.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arrow {
+    pub from: Square,
+    pub to: Square,
+    /// Any SVG color, e.g. `#15781b` or `red`.
+    pub color: String,
+}
+
+impl Arrow {
+    #[must_use]
+    pub fn new(from: Square, to: Square) -> Self {
+        Arrow {
+            from,
+            to,
+            color: DEFAULT_ARROW.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_color(mut self, color: &str) -> Self {
+        self.color = color.to_string();
+        self
+    }
+}
+
+/// Renders positions and bitboards as SVG. Layers are drawn in the order squares,
+/// highlights, pieces, annotations and arrows.
+#[derive(Debug, Clone)]
+pub struct BoardSvg {
+    orientation: Color,
+    coordinates: bool,
+    square_size: u32,
+    highlights: Vec<(Bitboard, String)>,
+    arrows: Vec<Arrow>,
+    annotations: Vec<(Square, String)>,
+}
+
+impl Default for BoardSvg {
+    fn default() -> Self {
+        BoardSvg {
+            orientation: Color::White,
+            coordinates: true,
+            square_size: 45,
+            highlights: Vec::new(),
+            arrows: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+}
+
+impl BoardSvg {
+    /// White at the bottom, with coordinates and 45 pixel squares.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Side shown at the bottom of the board.
+    #[must_use]
+    pub fn orientation(mut self, orientation: Color) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Whether file letters and rank numbers are drawn around the board.
+    #[must_use]
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    #[must_use]
+    pub fn square_size(mut self, square_size: u32) -> Self {
+        self.square_size = square_size;
+        self
+    }
+
+    /// Tints every square of the bitboard, e.g. the last move, attacks or pins. Later
+    /// highlights are drawn over earlier ones.
+    #[must_use]
+    pub fn highlight(mut self, squares: Bitboard, color: &str) -> Self {
+        self.highlights.push((squares, color.to_string()));
+        self
+    }
+
+    #[must_use]
+    pub fn arrow(mut self, arrow: Arrow) -> Self {
+        self.arrows.push(arrow);
+        self
+    }
+
+    /// Short text shown in the corner of the square, e.g. `!`, `?` or a number.
+    #[must_use]
+    pub fn annotate(mut self, square: Square, annotation: &str) -> Self {
+        self.annotations.push((square, annotation.to_string()));
+        self
+    }
+
+    #[must_use]
+    pub fn render(&self, position: &Position) -> String {
+        let mut svg = self.open();
+
+        let size = f64::from(self.square_size);
+        for (square, piece, color) in position.pieces() {
+            let (x, y) = self.square_origin(square);
+            let (fill, stroke) = match color {
+                Color::White => ("#fff", "#000"),
+                Color::Black => ("#000", "#000"),
+            };
+            writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" font-size="{:.1}" text-anchor="middle" dominant-baseline="central" fill="{fill}" stroke="{stroke}" stroke-width="1">{}</text>"#,
+                x + size / 2.0,
+                y + size / 2.0,
+                size * 0.8,
+                piece_glyph(piece),
+            )
+            .unwrap();
+        }
+
+        self.close(svg)
+    }
+
+    /// Renders the bitboard on an empty board, with its squares highlighted on top of any
+    /// other highlights.
+    #[must_use]
+    pub fn render_bitboard(&self, bitboard: Bitboard) -> String {
+        self.clone()
+            .highlight(bitboard, BITBOARD_HIGHLIGHT)
+            .render(&Position::empty())
+    }
+
+    /// Board, coordinates and highlights, the layers below the pieces.
+    fn open(&self) -> String {
+        let size = f64::from(self.square_size);
+        let board = self.margin() * 2.0 + size * 8.0;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {board:.1} {board:.1}" width="{board:.1}" height="{board:.1}">"#
+        )
+        .unwrap();
+
+        for square in Square::iter() {
+            let (x, y) = self.square_origin(square);
+            let light = (square.rank() as u8 + square.file() as u8) % 2 == 1;
+            let fill = if light { LIGHT_SQUARE } else { DARK_SQUARE };
+            writeln!(
+                svg,
+                r#"<rect x="{x:.1}" y="{y:.1}" width="{size:.1}" height="{size:.1}" fill="{fill}"/>"#
+            )
+            .unwrap();
+        }
+
+        if self.coordinates {
+            self.write_coordinates(&mut svg);
+        }
+
+        for (squares, color) in &self.highlights {
+            for square in *squares {
+                let (x, y) = self.square_origin(square);
+                writeln!(
+                    svg,
+                    r#"<rect x="{x:.1}" y="{y:.1}" width="{size:.1}" height="{size:.1}" fill="{}" fill-opacity="0.5"/>"#,
+                    escape(color)
+                )
+                .unwrap();
+            }
+        }
+
+        svg
+    }
+
+    /// Annotations and arrows, the layers above the pieces.
+    fn close(&self, mut svg: String) -> String {
+        let size = f64::from(self.square_size);
+
+        for (square, annotation) in &self.annotations {
+            let (x, y) = self.square_origin(*square);
+            writeln!(
+                svg,
+                r##"<text x="{:.1}" y="{:.1}" font-size="{:.1}" font-family="sans-serif" font-weight="bold" text-anchor="end" dominant-baseline="hanging" fill="#fff" stroke="#000" stroke-width="0.5">{}</text>"##,
+                x + size * 0.95,
+                y + size * 0.05,
+                size * 0.3,
+                escape(annotation)
+            )
+            .unwrap();
+        }
+
+        for arrow in &self.arrows {
+            self.write_arrow(&mut svg, arrow);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn write_coordinates(&self, svg: &mut String) {
+        let size = f64::from(self.square_size);
+        let margin = self.margin();
+
+        for file in File::ALL {
+            let (x, _) = self.square_origin(Square::from((file, Rank::R1)));
+            writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" font-size="{:.1}" font-family="sans-serif" text-anchor="middle" dominant-baseline="central">{file}</text>"#,
+                x + size / 2.0,
+                margin * 1.5 + size * 8.0,
+                margin * 0.6,
+            )
+            .unwrap();
+        }
+
+        for rank in Rank::ALL {
+            let (_, y) = self.square_origin(Square::from((File::A, rank)));
+            writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" font-size="{:.1}" font-family="sans-serif" text-anchor="middle" dominant-baseline="central">{rank}</text>"#,
+                margin / 2.0,
+                y + size / 2.0,
+                margin * 0.6,
+            )
+            .unwrap();
+        }
+    }
+
+    fn write_arrow(&self, svg: &mut String, arrow: &Arrow) {
+        let size = f64::from(self.square_size);
+        let color = escape(&arrow.color);
+        let center = |square: Square| {
+            let (x, y) = self.square_origin(square);
+            (x + size / 2.0, y + size / 2.0)
+        };
+        let (x1, y1) = center(arrow.from);
+        let (x2, y2) = center(arrow.to);
+
+        if arrow.from == arrow.to {
+            writeln!(
+                svg,
+                r#"<circle cx="{x1:.1}" cy="{y1:.1}" r="{:.1}" fill="none" stroke="{color}" stroke-width="{:.1}" opacity="0.8"/>"#,
+                size * 0.42,
+                size * 0.08,
+            )
+            .unwrap();
+            return;
+        }
+
+        // The shaft stops where the head starts, so the tip stays sharp.
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length = dx.hypot(dy);
+        let (ux, uy) = (dx / length, dy / length);
+        let head_length = size * 0.4;
+        let head_width = size * 0.2;
+        let (bx, by) = (x2 - ux * head_length, y2 - uy * head_length);
+
+        writeln!(
+            svg,
+            r#"<g fill="{color}" stroke="{color}" opacity="0.8"><line x1="{x1:.1}" y1="{y1:.1}" x2="{bx:.1}" y2="{by:.1}" stroke-width="{:.1}"/><polygon points="{x2:.1},{y2:.1} {:.1},{:.1} {:.1},{:.1}" stroke="none"/></g>"#,
+            size * 0.15,
+            bx - uy * head_width,
+            by + ux * head_width,
+            bx + uy * head_width,
+            by - ux * head_width,
+        )
+        .unwrap();
+    }
+
+    fn margin(&self) -> f64 {
+        if self.coordinates {
+            f64::from(self.square_size) / 2.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Top left corner of the square, taking the orientation into account.
+    fn square_origin(&self, square: Square) -> (f64, f64) {
+        let (column, row) = match self.orientation {
+            Color::White => (square.file() as u8, 7 - square.rank() as u8),
+            Color::Black => (7 - square.file() as u8, square.rank() as u8),
+        };
+        let size = f64::from(self.square_size);
+
+        (
+            self.margin() + f64::from(column) * size,
+            self.margin() + f64::from(row) * size,
+        )
+    }
+}
+
+fn piece_glyph(piece: Piece) -> char {
+    match piece {
+        Piece::Pawn => '♟',
+        Piece::Knight => '♞',
+        Piece::Bishop => '♝',
+        Piece::Rook => '♜',
+        Piece::Queen => '♛',
+        Piece::King => '♚',
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        position::{Color, Position},
+        square::Square,
+        svg::{Arrow, BoardSvg},
+    };
+
+    #[test]
+    fn test_render_position() {
+        let svg = BoardSvg::new().render(&Position::default());
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 405.0 405.0""#)
+        );
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 64);
+        assert_eq!(svg.matches('♟').count(), 16);
+        assert_eq!(
+            svg.matches(r##"fill="#fff" stroke="#000" stroke-width="1">"##)
+                .count(),
+            16
+        );
+
+        // The a1 rook is bottom left for white and top right for black.
+        assert!(svg.contains(r#"<text x="45.0" y="360.0""#));
+        let board = BoardSvg::new().orientation(Color::Black);
+        let flipped = board.coordinates(false).render(&Position::default());
+        assert!(flipped.contains(r#"viewBox="0 0 360.0 360.0""#));
+        assert!(flipped.contains(r#"<text x="337.5" y="22.5""#));
+    }
+
+    #[test]
+    fn test_overlays() {
+        let svg = BoardSvg::new()
+            .highlight(Square::E2.bitboard() | Square::E4.bitboard(), "yellow")
+            .arrow(Arrow::new(Square::E2, Square::E4).with_color("blue"))
+            .arrow(Arrow::new(Square::D5, Square::D5))
+            .annotate(Square::E4, "<!>")
+            .render(&Position::default());
+
+        assert_eq!(
+            svg.matches(r#"fill="yellow" fill-opacity="0.5""#).count(),
+            2
+        );
+        assert_eq!(svg.matches(r#"<g fill="blue""#).count(), 1);
+        assert_eq!(svg.matches("<circle").count(), 1);
+        assert!(svg.contains("&lt;!&gt;</text>"));
+
+        let bitboard = BoardSvg::new()
+            .coordinates(false)
+            .render_bitboard(Square::A1.bitboard() | Square::H8.bitboard());
+        assert_eq!(bitboard.matches("fill-opacity").count(), 2);
+        assert!(!bitboard.contains("♟"));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    position::{Color, Position},
+    square::Square,
+};
+
+impl Position {
+    /// The same position with colors swapped and ranks mirrored, e.g. white pieces on rank 2
+    /// become black pieces on rank 7. Side to move, castling rights and en passant are swapped
+    /// accordingly, so the result is equivalent to the original with the roles of the players
+    /// exchanged.
+    #[must_use]
+    pub fn color_flipped(&self) -> Position {
+        self.transformed(Square::flip_vertical, true)
+    }
+
+    /// The same position mirrored across the vertical axis, so file A becomes file H. Castling
+    /// rights aren't mirrored, as castling isn't symmetric.
+    #[must_use]
+    pub fn mirrored(&self) -> Position {
+        self.transformed(Square::mirror_horizontal, false)
+    }
+
+    /// A representative of all positions equivalent to this one under [`Position::color_flipped`]
+    /// and, when neither side can castle, [`Position::mirrored`]. Equivalent positions have the
+    /// same canonical form, which makes it suitable for deduplicating datasets.
+    #[must_use]
+    pub fn canonical(&self) -> Position {
+        let mut candidate = if self.turn == Color::White {
+            self.clone()
+        } else {
+            self.color_flipped()
+        };
+
+        if candidate.castling.is_empty() {
+            let mirrored = candidate.mirrored();
+            if mirrored.hash < candidate.hash {
+                candidate = mirrored;
+            }
+        }
+
+        candidate
+    }
+
+    fn transformed(&self, map_square: fn(Square) -> Square, swap_colors: bool) -> Position {
+        let mut position = Position::empty();
+
+        for (square, piece, color) in self.pieces() {
+            let color = if swap_colors { color.enemy() } else { color };
+            position
+                .add_piece_at(map_square(square), piece, color)
+                .expect("BUG: Transformed squares overlap");
+        }
+
+        if swap_colors {
+            position.turn = self.enemy();
+            position.castling = self.castling.color_flipped();
+        } else {
+            position.turn = self.turn;
+            position.castling = self.castling;
+        }
+        position.en_passant = self.en_passant.map(map_square);
+        position.halfmove_clock = self.halfmove_clock;
+        position.fullmove_number = self.fullmove_number;
+        position.refresh_hashes();
+
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fen::Fen,
+        position::{Color, Position},
+    };
+
+    fn pos(fen: &str) -> Position {
+        Position::from_fen(fen.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_color_flipped() {
+        let original = pos("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3");
+        let flipped = original.color_flipped();
+
+        assert_eq!(
+            flipped.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w Qk e6 0 3"
+        );
+        assert_eq!(flipped.color_flipped(), original);
+        assert!(flipped.validate().is_ok());
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let original = pos("8/2k5/3p4/p2P1p2/P2P1P2/8/8/4K3 w - - 0 1");
+
+        assert_eq!(
+            original.mirrored().to_fen(),
+            "8/5k2/4p3/2p1P2p/2P1P2P/8/8/3K4 w - - 0 1"
+        );
+        assert_eq!(original.mirrored().mirrored(), original);
+    }
+
+    #[test]
+    fn test_canonical() {
+        let original = pos("8/2k5/3p4/p2P1p2/P2P1P2/8/8/4K3 b - - 0 1");
+        let canonical = original.canonical();
+
+        for equivalent in [
+            original.color_flipped(),
+            original.mirrored(),
+            original.mirrored().color_flipped(),
+        ] {
+            assert_eq!(equivalent.canonical(), canonical);
+        }
+        assert_eq!(canonical.turn, Color::White);
+
+        let castling = pos("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(castling.canonical(), castling);
+    }
+}
@@ -0,0 +1,282 @@
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::{
+    bitboard::Bitboard,
+    lookup::pawns::PAWN_ATTACKS,
+    position::{CastlingKind, Color, Piece, Position},
+    square::{Rank, Square},
+};
+
+/// A single rule violated by a [`Position`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    MissingKing(Color),
+    TooManyKings(Color),
+    TooManyPawns(Color),
+    TooManyPieces(Color),
+    PawnOnBackRank(Square),
+    OverlappingPieces(Square),
+    CastlingWithoutKing(CastlingKind),
+    CastlingWithoutRook(CastlingKind),
+    InvalidEnPassant(Square),
+    OpponentInCheck,
+    TooManyCheckers,
+}
+
+/// Every rule violated by a [`Position`], as returned by [`Position::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPosition {
+    pub errors: Vec<ValidationError>,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValidationError::MissingKing(color) => write!(f, "{color:?} has no king"),
+            ValidationError::TooManyKings(color) => write!(f, "{color:?} has more than one king"),
+            ValidationError::TooManyPawns(color) => write!(f, "{color:?} has more than 8 pawns"),
+            ValidationError::TooManyPieces(color) => {
+                write!(f, "{color:?} has more than 16 pieces")
+            }
+            ValidationError::PawnOnBackRank(square) => write!(f, "Pawn on back rank: {square}"),
+            ValidationError::OverlappingPieces(square) => {
+                write!(f, "More than one piece at {square}")
+            }
+            ValidationError::CastlingWithoutKing(kind) => {
+                write!(f, "{kind:?} castling right without king on its home square")
+            }
+            ValidationError::CastlingWithoutRook(kind) => {
+                write!(f, "{kind:?} castling right without rook on its home square")
+            }
+            ValidationError::InvalidEnPassant(square) => {
+                write!(
+                    f,
+                    "En passant square {square} couldn't result from a double push"
+                )
+            }
+            ValidationError::OpponentInCheck => write!(f, "Side not to move is in check"),
+            ValidationError::TooManyCheckers => {
+                write!(f, "Side to move is attacked by more than two pieces")
+            }
+        }
+    }
+}
+
+impl Display for InvalidPosition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Invalid position: ")?;
+        for (idx, error) in self.errors.iter().enumerate() {
+            if idx != 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{error}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::error::Error for InvalidPosition {}
+
+impl Position {
+    /// Checks the position against the rules every position reachable from the standard starting
+    /// position obeys. `MoveGen` assumes these hold, e.g. that each side has exactly one king.
+    pub fn validate(&self) -> Result<(), InvalidPosition> {
+        let mut errors = Vec::new();
+
+        let mut seen = Bitboard::empty();
+        for color in Color::iter() {
+            for bb in self.pieces[color as usize] {
+                for square in bb & seen {
+                    errors.push(ValidationError::OverlappingPieces(square));
+                }
+                seen |= bb;
+            }
+        }
+
+        for color in Color::iter() {
+            let pieces = &self.pieces[color as usize];
+
+            match pieces[Piece::King as usize].count() {
+                0 => errors.push(ValidationError::MissingKing(color)),
+                1 => {}
+                _ => errors.push(ValidationError::TooManyKings(color)),
+            }
+
+            if pieces[Piece::Pawn as usize].count() > 8 {
+                errors.push(ValidationError::TooManyPawns(color));
+            }
+
+            if self.occupation(&color).count() > 16 {
+                errors.push(ValidationError::TooManyPieces(color));
+            }
+
+            let back_ranks = Rank::R1.bitboard() | Rank::R8.bitboard();
+            for square in pieces[Piece::Pawn as usize] & back_ranks {
+                errors.push(ValidationError::PawnOnBackRank(square));
+            }
+        }
+
+        for kind in CastlingKind::all() {
+            let Some(rook_file) = self.castling.rook_file(&kind) else {
+                continue;
+            };
+
+            // The king may stand on any file of the back rank, as in Chess960, with the rook
+            // on the side of the right.
+            let color = kind.color();
+            let king = self.pieces[color as usize][Piece::King as usize];
+            let king_placed = king.count() == 1 && king.lsb().rank() == kind.back_rank();
+            if !king_placed {
+                errors.push(ValidationError::CastlingWithoutKing(kind));
+            }
+
+            let rook_square = Square::from((rook_file, kind.back_rank()));
+            if !self.pieces[color as usize][Piece::Rook as usize].has(rook_square)
+                || king_placed && self.castling_kind_for_rook(rook_square, color) != Some(kind)
+            {
+                errors.push(ValidationError::CastlingWithoutRook(kind));
+            }
+        }
+
+        if let Some(square) = self.en_passant {
+            if !self.is_valid_en_passant(square) {
+                errors.push(ValidationError::InvalidEnPassant(square));
+            }
+        }
+
+        let enemy_king = self.pieces[self.enemy() as usize][Piece::King as usize];
+        if enemy_king.count() == 1 && !self.attackers(enemy_king.lsb(), self.turn).is_empty() {
+            errors.push(ValidationError::OpponentInCheck);
+        }
+
+        let king = self.pieces[self.turn as usize][Piece::King as usize];
+        if king.count() == 1 && self.attackers(king.lsb(), self.enemy()).count() > 2 {
+            errors.push(ValidationError::TooManyCheckers);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidPosition { errors })
+        }
+    }
+
+    pub(crate) fn is_valid_en_passant(&self, square: Square) -> bool {
+        // Rank offset towards the side that made the double push.
+        let (rank, direction) = match self.turn {
+            Color::White => (Rank::R6, -1),
+            Color::Black => (Rank::R3, 1),
+        };
+
+        if square.rank() != rank {
+            return false;
+        }
+
+        let (Some(pushed), Some(origin)) =
+            (square.offset(direction, 0), square.offset(-direction, 0))
+        else {
+            return false;
+        };
+
+        self.pieces[self.enemy() as usize][Piece::Pawn as usize].has(pushed)
+            && !self.occupied.has(square)
+            && !self.occupied.has(origin)
+    }
+
+    /// Whether a pawn of the side to move attacks the en passant square. Moves only set the
+    /// square in that case, so positions built otherwise must drop it to hash the same.
+    pub(crate) fn can_take_en_passant(&self, square: Square) -> bool {
+        let pawns = self.pieces[self.turn as usize][Piece::Pawn as usize];
+
+        !(PAWN_ATTACKS[self.enemy() as usize][square as usize] & pawns).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fen::Fen,
+        position::{CastlingKind, Color, Position},
+        square::Square,
+        validation::ValidationError,
+    };
+
+    fn errors(fen: &str) -> Vec<ValidationError> {
+        Position::from_fen(fen.to_string())
+            .unwrap()
+            .validate()
+            .err()
+            .map(|invalid| invalid.errors)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_valid_positions() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/8/8/8/8/8/8/k1K5 w - - 0 1",
+        ] {
+            assert_eq!(errors(fen), vec![], "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_positions() {
+        assert_eq!(
+            errors("8/8/8/8/8/8/8/K7 w - - 0 1"),
+            vec![ValidationError::MissingKing(Color::Black)]
+        );
+        assert_eq!(
+            errors("k7/8/8/8/8/8/8/K1K5 w - - 0 1"),
+            vec![ValidationError::TooManyKings(Color::White)]
+        );
+        assert_eq!(
+            errors("k6P/8/8/8/8/8/8/K7 w - - 0 1"),
+            vec![ValidationError::PawnOnBackRank(Square::H8)]
+        );
+        assert_eq!(
+            errors("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1"),
+            vec![ValidationError::CastlingWithoutRook(
+                CastlingKind::WhiteKingside
+            )]
+        );
+        assert_eq!(
+            errors("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1"),
+            vec![ValidationError::InvalidEnPassant(Square::E3)]
+        );
+        assert_eq!(
+            errors("k7/8/8/8/8/8/8/K6r b - - 0 1"),
+            vec![ValidationError::OpponentInCheck]
+        );
+        assert_eq!(
+            errors("k6R/8/8/8/8/8/8/K7 w - - 0 1"),
+            vec![ValidationError::OpponentInCheck]
+        );
+    }
+
+    #[test]
+    fn test_collects_every_error() {
+        let errors = errors("8/8/8/8/8/8/8/R3P3 w Q e6 0 1");
+
+        assert!(errors.contains(&ValidationError::MissingKing(Color::White)));
+        assert!(errors.contains(&ValidationError::MissingKing(Color::Black)));
+        assert!(errors.contains(&ValidationError::PawnOnBackRank(Square::E1)));
+        assert!(errors.contains(&ValidationError::CastlingWithoutKing(
+            CastlingKind::WhiteQueenside
+        )));
+        assert!(errors.contains(&ValidationError::InvalidEnPassant(Square::E6)));
+    }
+
+    #[test]
+    fn test_strict_fen() {
+        assert!(Position::from_fen_strict("8/8/8/8/8/8/8/K7 w - - 0 1".to_string()).is_err());
+        assert!(Position::from_fen_strict(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    position::{Castling, Color, Piece, Position},
+    square::Square,
+};
+
+/// Keys indexed by `[color][piece][square]`. The material key reuses them indexed by piece count
+/// instead of square.
+pub static PIECE_KEYS: [[[u64; 64]; 6]; 2] = generate_piece_keys();
+/// Keys indexed by the 4-bit castling rights mask.
+pub static CASTLING_KEYS: [u64; 16] = generate_keys::<16>(PIECE_KEY_COUNT);
+/// Keys indexed by the file of the en passant square.
+pub static EN_PASSANT_KEYS: [u64; 8] = generate_keys::<8>(PIECE_KEY_COUNT + 16);
+/// Key toggled when black is to move.
+pub const SIDE_KEY: u64 = key(PIECE_KEY_COUNT + 24);
+
+const PIECE_KEY_COUNT: u64 = 2 * 6 * 64;
+
+/// `SplitMix64` output for the given index, so every key is fixed at compile time.
+const fn key(index: u64) -> u64 {
+    let mut z = index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    z ^ (z >> 31)
+}
+
+const fn generate_keys<const N: usize>(offset: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut i = 0;
+    while i < N {
+        keys[i] = key(offset + i as u64);
+        i += 1;
+    }
+    keys
+}
+
+const fn generate_piece_keys() -> [[[u64; 64]; 6]; 2] {
+    let mut keys = [[[0; 64]; 6]; 2];
+    let mut color = 0;
+    while color < 2 {
+        let mut piece = 0;
+        while piece < 6 {
+            let mut square = 0;
+            while square < 64 {
+                keys[color][piece][square] = key(((color * 6 + piece) * 64 + square) as u64);
+                square += 1;
+            }
+            piece += 1;
+        }
+        color += 1;
+    }
+    keys
+}
+
+#[must_use]
+pub fn piece_key(piece: Piece, color: Color, square: Square) -> u64 {
+    PIECE_KEYS[color as usize][piece as usize][square as usize]
+}
+
+#[must_use]
+pub fn castling_key(castling: Castling) -> u64 {
+    CASTLING_KEYS[castling.bits() as usize]
+}
+
+#[must_use]
+pub fn en_passant_key(en_passant: Option<Square>) -> u64 {
+    en_passant.map_or(0, |square| EN_PASSANT_KEYS[square.file() as usize])
+}
+
+impl Position {
+    /// Computes the Zobrist key of the position from scratch. `make_move` keeps `hash` up to date
+    /// incrementally, this is meant for initialization and verification.
+    #[must_use]
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = castling_key(self.castling) ^ en_passant_key(self.en_passant);
+        if self.turn == Color::Black {
+            hash ^= SIDE_KEY;
+        }
+
+        for color in Color::iter() {
+            for piece in Piece::all() {
+                for square in self.pieces[color as usize][piece as usize] {
+                    hash ^= piece_key(piece, color, square);
+                }
+            }
+        }
+
+        hash
+    }
+
+    /// Computes the key of the pawn structure of both sides.
+    #[must_use]
+    pub fn compute_pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+        for color in Color::iter() {
+            for square in self.pieces[color as usize][Piece::Pawn as usize] {
+                hash ^= piece_key(Piece::Pawn, color, square);
+            }
+        }
+
+        hash
+    }
+
+    /// Computes the key of the piece counts of both sides, independent of piece placement.
+    #[must_use]
+    pub fn compute_material_hash(&self) -> u64 {
+        let mut hash = 0;
+        for color in Color::iter() {
+            for piece in Piece::all() {
+                let count = self.pieces[color as usize][piece as usize].count();
+                for idx in 0..count {
+                    hash ^= PIECE_KEYS[color as usize][piece as usize][idx as usize];
+                }
+            }
+        }
+
+        hash
+    }
+
+    /// Recomputes all keys. Needed after modifying `pieces`, `castling` or `en_passant` directly.
+    pub fn refresh_hashes(&mut self) {
+        self.hash = self.compute_hash();
+        self.pawn_hash = self.compute_pawn_hash();
+        self.material_hash = self.compute_material_hash();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fen::Fen,
+        position::{Color, Piece, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_hash_depends_on_all_state() {
+        let start = Position::default();
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Kkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKB1R w KQkq - 0 1",
+        ];
+
+        for fen in fens {
+            let pos = Position::from_fen(fen.to_string()).unwrap();
+            assert_ne!(start.hash, pos.hash, "{fen}");
+        }
+
+        let later = Position::from_fen(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 12 40".to_string(),
+        )
+        .unwrap();
+        assert_eq!(start.hash, later.hash);
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut pos = Position::default();
+        let material = pos.material_hash;
+
+        let (piece, color) = pos.remove_piece_at(&Square::E2).unwrap();
+        pos.add_piece_at(Square::E4, piece, color).unwrap();
+        let _ = pos.swap_turn();
+        pos.set_en_passant(Some(Square::E3));
+
+        assert_eq!(pos.hash, pos.compute_hash());
+        assert_eq!(pos.pawn_hash, pos.compute_pawn_hash());
+        assert_eq!(pos.material_hash, material);
+
+        pos.remove_piece_at(&Square::D8);
+        pos.add_piece_at(Square::D8, Piece::Queen, Color::White)
+            .unwrap();
+
+        assert_eq!(pos.hash, pos.compute_hash());
+        assert_eq!(pos.material_hash, pos.compute_material_hash());
+        assert_ne!(pos.material_hash, material);
+    }
+}